@@ -0,0 +1,6 @@
+// Code shared by the command-line game in `tic-tac-toe` and the Bevy game in
+// `tic-tac-toe-visual`, so both name cells, search for moves and draw SVG images the same way.
+
+pub mod notation;
+pub mod search;
+pub mod svg;
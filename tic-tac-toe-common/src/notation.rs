@@ -0,0 +1,29 @@
+// Algebraic cell names: a column letter followed by a row number counted from 1 at the bottom,
+// e.g. "b2". The two games store rows in opposite orders, so they pass the row number in.
+
+// Returns the letter that labels a column, 'a' for the leftmost
+pub fn file_label(col: usize) -> char {
+    (b'a' + col as u8) as char
+}
+
+// Names the cell in column `col` on row `rank`, counted from 1 at the bottom
+pub fn cell_name(col: usize, rank: usize) -> String {
+    format!("{}{}", file_label(col), rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_are_lettered_from_the_left() {
+        assert_eq!(file_label(0), 'a');
+        assert_eq!(file_label(25), 'z');
+    }
+
+    #[test]
+    fn cells_are_named_by_column_and_rank() {
+        assert_eq!(cell_name(1, 2), "b2");
+        assert_eq!(cell_name(9, 10), "j10");
+    }
+}
@@ -0,0 +1,346 @@
+// The minimax search both games use for their computer players and hints. It runs a negamax
+// search with alpha-beta pruning to a given depth; `affordable_depth` gives the deepest search
+// that stays within a fixed number of positions, which is the whole game on small boards.
+// Positions where the search stops are scored by counting the lines each side can still
+// complete, so a shallow search still blocks threats it cannot see to the end.
+// Boards are given row by row; the search does not care which way up they are drawn.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const WIN_SCORE: i32 = 1000;
+const MAX_ESTIMATE: i32 = WIN_SCORE / 2; // Estimates stay below any forced win or loss
+const NODE_BUDGET: usize = 1_000_000; // Rough limit on the move sequences a search may visit
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+// The rules a search plays by
+pub struct Search<'a> {
+    pub marks: [char; 2], // The marks of the two sides
+    pub win_length: usize,
+    pub misere: bool, // Completing a line loses instead of winning
+    // Once the flag is set the search gives up, and its answer is meaningless
+    pub cancelled: Option<&'a AtomicBool>,
+}
+
+impl Search<'_> {
+    // Scores every empty cell for `side` to move, searching `depth` moves ahead, and returns the
+    // cells with the best score, or nothing if the board is full
+    pub fn best_moves(
+        &self,
+        grid: &[Vec<Option<char>>],
+        side: usize,
+        depth: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut grid = grid.to_vec();
+        let estimate = self.estimate(&grid);
+        let depth = depth.max(1);
+
+        let moves = ordered_moves(&grid);
+        let scores: Vec<i32> = moves
+            .iter()
+            .map(|&cell| {
+                self.score_move(
+                    &mut grid,
+                    side,
+                    cell,
+                    depth,
+                    estimate,
+                    -WIN_SCORE * 2,
+                    WIN_SCORE * 2,
+                )
+            })
+            .collect();
+        let Some(&best) = scores.iter().max() else {
+            return Vec::new();
+        };
+        moves
+            .into_iter()
+            .zip(scores)
+            .filter(|&(_, score)| score == best)
+            .map(|(cell, _)| cell)
+            .collect()
+    }
+
+    // Plays a move for `side`, scores the resulting position from that side's point of view, and
+    // takes it back. `estimate` is the line count of the position before the move.
+    #[allow(clippy::too_many_arguments)]
+    fn score_move(
+        &self,
+        grid: &mut [Vec<Option<char>>],
+        side: usize,
+        (row, col): (usize, usize),
+        depth: usize,
+        estimate: i32,
+        alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        // Only the lines through the new mark change
+        let before = self.lines_through(grid, row, col);
+        grid[row][col] = Some(self.marks[side]);
+        let estimate = estimate - before + self.lines_through(grid, row, col);
+        let score = if has_line_through(grid, row, col, self.win_length) {
+            // Completing a line ends the game; quicker results score further from zero
+            let score = WIN_SCORE + empty_cells(grid).len() as i32;
+            if self.misere {
+                -score
+            } else {
+                score
+            }
+        } else {
+            -self.negamax(grid, 1 - side, depth - 1, estimate, -beta, -alpha)
+        };
+        grid[row][col] = None;
+        score
+    }
+
+    // Scores the position for `side` to move
+    fn negamax(
+        &self,
+        grid: &mut [Vec<Option<char>>],
+        side: usize,
+        depth: usize,
+        estimate: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if grid.iter().flatten().all(Option::is_some) {
+            return 0;
+        }
+        if depth == 0 {
+            // Open lines help the side that owns them, unless completing a line loses
+            let estimate = if self.misere { -estimate } else { estimate };
+            let estimate = if side == 0 { estimate } else { -estimate };
+            return estimate.clamp(-MAX_ESTIMATE, MAX_ESTIMATE);
+        }
+
+        let mut best = -WIN_SCORE * 2;
+        for cell in ordered_moves(grid) {
+            if self.is_cancelled() {
+                break;
+            }
+            let score = self.score_move(grid, side, cell, depth, estimate, alpha, beta);
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break; // The opponent will not allow this line
+            }
+        }
+        best
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    // Scores the position from side 0's point of view by counting the lines of `win_length`
+    // cells that only one side has marks in. Lines count for more the fuller they are.
+    fn estimate(&self, grid: &[Vec<Option<char>>]) -> i32 {
+        let size = grid.len() as isize;
+        let mut total = 0;
+        for row in 0..size {
+            for col in 0..size {
+                for direction in DIRECTIONS {
+                    total += self.line_value(grid, (row, col), direction);
+                }
+            }
+        }
+        total
+    }
+
+    // Sums `line_value` over the lines that pass through (row, col)
+    fn lines_through(&self, grid: &[Vec<Option<char>>], row: usize, col: usize) -> i32 {
+        let mut total = 0;
+        for (dr, dc) in DIRECTIONS {
+            for back in 0..self.win_length as isize {
+                let start = (row as isize - back * dr, col as isize - back * dc);
+                total += self.line_value(grid, start, (dr, dc));
+            }
+        }
+        total
+    }
+
+    // Values the line of `win_length` cells from `start` in `direction`: the square of the number
+    // of marks if they all belong to one side, positive for side 0 and negative for side 1. Lines
+    // that run off the board or hold both marks are worth nothing.
+    fn line_value(
+        &self,
+        grid: &[Vec<Option<char>>],
+        start: (isize, isize),
+        direction: (isize, isize),
+    ) -> i32 {
+        let size = grid.len() as isize;
+        let length = self.win_length as isize;
+        let end = (
+            start.0 + (length - 1) * direction.0,
+            start.1 + (length - 1) * direction.1,
+        );
+        let inside =
+            |(row, col): (isize, isize)| (0..size).contains(&row) && (0..size).contains(&col);
+        if !inside(start) || !inside(end) {
+            return 0;
+        }
+
+        let mut counts = [0, 0];
+        for step in 0..length {
+            let row = (start.0 + step * direction.0) as usize;
+            let col = (start.1 + step * direction.1) as usize;
+            match grid[row][col] {
+                Some(mark) if mark == self.marks[0] => counts[0] += 1,
+                Some(_) => counts[1] += 1,
+                None => {}
+            }
+        }
+        match counts {
+            [first, 0] => first * first,
+            [0, second] => -(second * second),
+            _ => 0,
+        }
+    }
+}
+
+// Deepest search whose move sequences fit in the node budget, given the number of empty cells
+pub fn affordable_depth(empty: usize) -> usize {
+    let mut nodes = 1;
+    let mut depth = 0;
+    while depth < empty {
+        nodes *= empty - depth;
+        if nodes > NODE_BUDGET {
+            break;
+        }
+        depth += 1;
+    }
+    depth.max(1)
+}
+
+fn empty_cells(grid: &[Vec<Option<char>>]) -> Vec<(usize, usize)> {
+    let size = grid.len();
+    (0..size)
+        .flat_map(|row| (0..size).map(move |col| (row, col)))
+        .filter(|&(row, col)| grid[row][col].is_none())
+        .collect()
+}
+
+// Empty cells sorted from the centre outwards, so the search tries the strongest moves first
+fn ordered_moves(grid: &[Vec<Option<char>>]) -> Vec<(usize, usize)> {
+    let centre = (grid.len() as isize - 1) / 2;
+    let mut cells = empty_cells(grid);
+    cells.sort_by_key(|&(row, col)| {
+        (row as isize - centre)
+            .abs()
+            .max((col as isize - centre).abs())
+    });
+    cells
+}
+
+// Checks whether the mark at (row, col) is part of `length` identical marks in a row, column or
+// diagonal
+fn has_line_through(grid: &[Vec<Option<char>>], row: usize, col: usize, length: usize) -> bool {
+    let Some(mark) = grid[row][col] else {
+        return false;
+    };
+    let size = grid.len() as isize;
+    let run = |dr: isize, dc: isize| {
+        let (mut r, mut c) = (row as isize + dr, col as isize + dc);
+        let mut count = 0;
+        while (0..size).contains(&r)
+            && (0..size).contains(&c)
+            && grid[r as usize][c as usize] == Some(mark)
+        {
+            count += 1;
+            r += dr;
+            c += dc;
+        }
+        count
+    };
+    DIRECTIONS
+        .iter()
+        .any(|&(dr, dc)| 1 + run(dr, dc) + run(-dr, -dc) >= length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Vec<Vec<Option<char>>> {
+        rows.iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| if c == '.' { None } else { Some(c) })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn search(win_length: usize) -> Search<'static> {
+        Search {
+            marks: ['X', 'O'],
+            win_length,
+            misere: false,
+            cancelled: None,
+        }
+    }
+
+    #[test]
+    fn small_boards_are_searched_to_the_end() {
+        assert_eq!(affordable_depth(9), 9);
+        assert!(affordable_depth(81) < 81);
+        assert_eq!(affordable_depth(0), 1);
+    }
+
+    #[test]
+    fn wins_are_taken_and_losses_blocked() {
+        let board = grid(&["XX.", "OO.", "..."]);
+        assert_eq!(search(3).best_moves(&board, 0, 9), [(0, 2)]);
+        assert_eq!(search(3).best_moves(&board, 1, 9), [(1, 2)]);
+        assert!(search(3)
+            .best_moves(&grid(&["XOX", "XOO", "OXX"]), 1, 9)
+            .is_empty());
+    }
+
+    #[test]
+    fn misere_avoids_completing_a_line() {
+        let misere = Search {
+            misere: true,
+            ..search(3)
+        };
+        // Any other cell leaves O a move that does not complete a line
+        let moves = misere.best_moves(&grid(&["XX.", "O.O", "X.O"]), 0, 9);
+        assert!(!moves.contains(&(0, 2)), "X played {:?}", moves);
+    }
+
+    #[test]
+    fn open_lines_are_blocked_beyond_the_search_depth() {
+        // X threatens to make an open four on the fifth row, which wins two moves later than a
+        // shallow search can see
+        let mut board = vec![vec![None; 9]; 9];
+        board[4][2..5].fill(Some('X'));
+        board[8][0] = Some('O');
+        board[7][0] = Some('O');
+        let moves = search(5).best_moves(&board, 1, 2);
+        assert!(
+            moves.iter().all(|&cell| cell == (4, 1) || cell == (4, 5)),
+            "O played {:?}",
+            moves
+        );
+    }
+
+    #[test]
+    fn estimates_count_lines_only_one_side_can_complete() {
+        // The centre lies on four lines
+        assert_eq!(search(3).estimate(&grid(&["...", ".X.", "..."])), 4);
+        // The diagonal through both marks no longer counts for X
+        assert_eq!(search(3).estimate(&grid(&["...", ".X.", "O.."])), 3 - 2);
+    }
+
+    #[test]
+    fn a_cancelled_search_gives_up() {
+        let stop = AtomicBool::new(true);
+        let cancelled = Search {
+            cancelled: Some(&stop),
+            ..search(5)
+        };
+        // Without the flag this would take far longer than a test can wait
+        let moves = cancelled.best_moves(&vec![vec![None; 9]; 9], 0, 81);
+        assert!(!moves.is_empty());
+    }
+}
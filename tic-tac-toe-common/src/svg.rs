@@ -0,0 +1,310 @@
+// Draws boards as standalone SVG images, for documentation and bug reports. A single position is
+// drawn with its coordinates and the winning line; a whole game becomes a filmstrip with the board
+// after every move. The images are built as text, so nothing here needs a terminal or a GPU.
+// Boards are given row by row from the top; each game converts its own grid first.
+
+use crate::notation::file_label;
+
+const CELL: usize = 60; // Width and height of a cell in pixels
+const LABEL: usize = 24; // Room for the row numbers on the left and the column letters below
+const PAD: usize = 12; // Empty space around each board
+const CAPTION: usize = 28; // Room for the caption above each board of a filmstrip
+const FRAMES_PER_ROW: usize = 5; // Boards side by side in a filmstrip
+
+// The first and last cells of a line, as (row, col) counted from the top left
+pub type Line = ((usize, usize), (usize, usize));
+
+// Colours as SVG colour strings such as "#ff0000"
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style {
+    pub marks: [char; 2], // Marks of the first and second player, drawn in `mark_colors`
+    pub mark_colors: [String; 2],
+    pub background: String,
+    pub light_cell: String, // Cells alternate between the light and dark colours
+    pub dark_cell: String,
+    pub grid_line: String,
+    pub winning_line: String,
+    pub text: String, // Coordinates and captions
+}
+
+impl Default for Style {
+    // Red X and blue O on white, as in the terminal game
+    fn default() -> Self {
+        Style {
+            marks: ['X', 'O'],
+            mark_colors: ["#ff0000".to_string(), "#0000ff".to_string()],
+            background: "#ffffff".to_string(),
+            light_cell: "#ffffff".to_string(),
+            dark_cell: "#ffffff".to_string(),
+            grid_line: "#333333".to_string(),
+            winning_line: "#1a1a1a".to_string(),
+            text: "#333333".to_string(),
+        }
+    }
+}
+
+// One board of a filmstrip
+pub struct Frame {
+    pub cells: Vec<Vec<Option<char>>>, // Rows from the top
+    pub line: Option<Line>,
+    pub caption: String,
+}
+
+// Renders one position
+pub fn board(cells: &[Vec<Option<char>>], line: Option<Line>, style: &Style) -> String {
+    let (width, height) = frame_size(cells.len());
+    let mut out = header(width, height, style);
+    out += &draw_board(cells, line, style, 0, 0);
+    out += "</svg>\n";
+    out
+}
+
+// Renders the frames left to right and top to bottom, each with its caption above it. An empty
+// list draws nothing but the background.
+pub fn filmstrip(frames: &[Frame], style: &Style) -> String {
+    let size = frames.first().map_or(0, |frame| frame.cells.len());
+    let columns = frames.len().clamp(1, FRAMES_PER_ROW);
+    let rows = frames.len().div_ceil(FRAMES_PER_ROW).max(1);
+
+    let (width, height) = frame_size(size);
+    let frame_height = height + CAPTION;
+    let mut out = header(width * columns, frame_height * rows, style);
+    for (index, frame) in frames.iter().enumerate() {
+        let x = (index % FRAMES_PER_ROW) * width;
+        let y = (index / FRAMES_PER_ROW) * frame_height;
+        out += &format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"18\" fill=\"{}\">{}</text>\n",
+            x + PAD + LABEL,
+            y + CAPTION - 6,
+            style.text,
+            escape(&frame.caption)
+        );
+        out += &draw_board(&frame.cells, frame.line, style, x, y + CAPTION);
+    }
+    out += "</svg>\n";
+    out
+}
+
+fn frame_size(size: usize) -> (usize, usize) {
+    let side = 2 * PAD + LABEL + size * CELL;
+    (side, side)
+}
+
+fn header(width: usize, height: usize, style: &Style) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n\
+         <rect width=\"{0}\" height=\"{1}\" fill=\"{2}\"/>\n",
+        width, height, style.background
+    )
+}
+
+// Draws a board with its top-left corner (including the padding) at (x, y)
+fn draw_board(
+    cells: &[Vec<Option<char>>],
+    line: Option<Line>,
+    style: &Style,
+    x: usize,
+    y: usize,
+) -> String {
+    let size = cells.len();
+    let left = x + PAD + LABEL;
+    let top = y + PAD;
+    let side = size * CELL;
+    let centre =
+        |row: usize, col: usize| (left + col * CELL + CELL / 2, top + row * CELL + CELL / 2);
+    let mut out = String::new();
+
+    // Cells, alternating from a light one in the bottom left corner
+    for row in 0..size {
+        for col in 0..size {
+            let color = if (size - 1 - row + col).is_multiple_of(2) {
+                &style.light_cell
+            } else {
+                &style.dark_cell
+            };
+            out += &format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                left + col * CELL,
+                top + row * CELL,
+                CELL,
+                CELL,
+                color
+            );
+        }
+    }
+
+    // Grid lines, including the outer border
+    for i in 0..=size {
+        let offset = i * CELL;
+        out += &format!(
+            "<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"{3}\" stroke-width=\"2\"/>\n",
+            left + offset,
+            top,
+            top + side,
+            style.grid_line
+        );
+        out += &format!(
+            "<line x1=\"{0}\" y1=\"{1}\" x2=\"{2}\" y2=\"{1}\" stroke=\"{3}\" stroke-width=\"2\"/>\n",
+            left,
+            top + offset,
+            left + side,
+            style.grid_line
+        );
+    }
+
+    // Row numbers on the left, counted from the bottom, and column letters below
+    for i in 0..size {
+        let (cx, cy) = centre(i, i);
+        out += &label(left - LABEL / 2, cy, &(size - i).to_string(), style);
+        out += &label(
+            cx,
+            top + side + LABEL / 2,
+            &file_label(i).to_string(),
+            style,
+        );
+    }
+
+    for (row, marks) in cells.iter().enumerate() {
+        for (col, cell) in marks.iter().enumerate() {
+            if let Some(mark) = *cell {
+                let (cx, cy) = centre(row, col);
+                let color = if mark == style.marks[0] {
+                    &style.mark_colors[0]
+                } else {
+                    &style.mark_colors[1]
+                };
+                out += &format!(
+                    "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-weight=\"bold\" \
+                     font-size=\"{}\" fill=\"{}\" text-anchor=\"middle\" \
+                     dominant-baseline=\"central\">{}</text>\n",
+                    cx,
+                    cy,
+                    CELL * 7 / 10,
+                    color,
+                    escape(&mark.to_string())
+                );
+            }
+        }
+    }
+
+    if let Some((start, end)) = line {
+        let (x1, y1) = centre(start.0, start.1);
+        let (x2, y2) = centre(end.0, end.1);
+        out += &format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"6\" \
+             stroke-linecap=\"round\"/>\n",
+            x1, y1, x2, y2, style.winning_line
+        );
+    }
+    out
+}
+
+fn label(x: usize, y: usize, value: &str, style: &Style) -> String {
+    format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"14\" fill=\"{}\" \
+         text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+        x, y, style.text, value
+    )
+}
+
+// Marks and captions can contain characters chosen by the players, so they are escaped before
+// going into the XML
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '<' => out += "&lt;",
+            '>' => out += "&gt;",
+            '&' => out += "&amp;",
+            '"' => out += "&quot;",
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Vec<Vec<Option<char>>> {
+        rows.iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| if c == '.' { None } else { Some(c) })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn boards_show_marks_coordinates_and_the_winning_line() {
+        let image = board(
+            &grid(&["XXX", ".O.", "O.."]),
+            Some(((0, 0), (0, 2))),
+            &Style::default(),
+        );
+        assert!(image.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"228\""));
+        assert!(image.ends_with("</svg>\n"));
+        assert_eq!(image.matches(">X</text>").count(), 3);
+        assert_eq!(image.matches(">O</text>").count(), 2);
+        for coordinate in ["a", "b", "c", "1", "2", "3"] {
+            assert!(image.contains(&format!(">{}</text>", coordinate)));
+        }
+        // The line joins the centres of the top left and top right cells
+        assert!(image.contains(
+            "<line x1=\"66\" y1=\"42\" x2=\"186\" y2=\"42\" stroke=\"#1a1a1a\" stroke-width=\"6\""
+        ));
+    }
+
+    #[test]
+    fn marks_take_the_colour_of_their_player() {
+        let style = Style {
+            marks: ['#', '<'],
+            ..Style::default()
+        };
+        let image = board(&grid(&["#<.", "...", "..."]), None, &style);
+        assert!(image.contains(
+            "fill=\"#ff0000\" text-anchor=\"middle\" dominant-baseline=\"central\">#</text>"
+        ));
+        assert!(image.contains(
+            "fill=\"#0000ff\" text-anchor=\"middle\" dominant-baseline=\"central\">&lt;</text>"
+        ));
+    }
+
+    #[test]
+    fn cells_alternate_from_the_bottom_left() {
+        let style = Style {
+            light_cell: "#eeeeee".to_string(),
+            dark_cell: "#999999".to_string(),
+            ..Style::default()
+        };
+        let image = board(&grid(&["..", ".."]), None, &style);
+        let fills: Vec<&str> = image
+            .lines()
+            .filter(|line| line.starts_with("<rect x="))
+            .map(|line| line.split("fill=\"").nth(1).unwrap())
+            .collect();
+        // Top left, top right, bottom left, bottom right
+        assert_eq!(
+            fills,
+            ["#999999\"/>", "#eeeeee\"/>", "#eeeeee\"/>", "#999999\"/>"]
+        );
+    }
+
+    #[test]
+    fn filmstrips_wrap_after_five_frames() {
+        let frames: Vec<Frame> = (0..7)
+            .map(|index| Frame {
+                cells: grid(&["...", "...", "..."]),
+                line: None,
+                caption: format!("{}. X & O", index + 1),
+            })
+            .collect();
+        let image = filmstrip(&frames, &Style::default());
+        // Five frames of 228 pixels across, two rows of 256 pixels down
+        assert!(image.contains("width=\"1140\" height=\"512\""));
+        assert!(image.contains(">7. X &amp; O</text>"));
+        assert_eq!(image.matches("stroke-width=\"6\"").count(), 0);
+    }
+}
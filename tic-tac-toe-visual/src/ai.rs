@@ -0,0 +1,318 @@
+// Move selection for the computer players. Each side is played by a person or by an engine:
+// random moves, the trained network or a minimax search; the search, shared with the command-line
+// game, follows the current rules (win length and variant) and looks further ahead at higher
+// difficulties. Moves are searched for on a background task so the window keeps drawing while
+// the computer thinks.
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tic_tac_toe_common::search::{affordable_depth, Search};
+
+use crate::game::{AiNetwork, GameEvents, GameReset, GameState, GridConfig, Variant};
+use crate::menu::GameMode;
+use crate::nn::Network;
+use crate::online::Role;
+
+const EASY_RANDOM_CHANCE: f64 = 0.4; // How often the easy opponent plays a random move
+const NETWORK_SIMULATIONS: usize = 400; // Tree search rounds the network engine runs per move
+const PACES: [f32; 6] = [0.0, 0.1, 0.25, 0.5, 1.0, 2.0]; // Move delays the speed keys step through
+
+// Plays a side in the demo when the settings give it to a person
+const DEMO_ENGINE: Engine = Engine {
+    opponent: Opponent::Minimax,
+    difficulty: Difficulty::Medium,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opponent {
+    Random,  // Plays any empty cell
+    Network, // Tree search guided by the trained network; minimax if it does not fit the rules
+    Minimax, // Searches ahead as far as the difficulty allows
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+// How the computer picks its moves
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Engine {
+    pub opponent: Opponent,
+    pub difficulty: Difficulty,
+}
+
+// Who plays a side
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Controller {
+    Human,
+    Ai(Engine),
+    Remote, // The other player of an online game, whose moves arrive over the network
+}
+
+// Who plays each side when a game is started from "Versus AI", and how fast the computer moves
+#[derive(Resource, Clone, Copy)]
+pub struct AiSettings {
+    pub x: Controller,
+    pub o: Controller,
+    pub min_think_seconds: f32, // Moves never appear sooner than this, however quick the search
+}
+
+// Who plays each side in the current game, picked from the game mode and the settings
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Controllers {
+    pub x: Controller,
+    pub o: Controller,
+}
+
+impl Controllers {
+    pub fn new(mode: GameMode, settings: &AiSettings) -> Self {
+        let demo = |controller| match controller {
+            Controller::Human => Controller::Ai(DEMO_ENGINE),
+            ai => ai,
+        };
+        match mode {
+            GameMode::HotSeat => Controllers {
+                x: Controller::Human,
+                o: Controller::Human,
+            },
+            GameMode::VsAi => Controllers {
+                x: settings.x,
+                o: settings.o,
+            },
+            GameMode::Demo => Controllers {
+                x: demo(settings.x),
+                o: demo(settings.o),
+            },
+            // The host plays X
+            GameMode::Online(Role::Host) => Controllers {
+                x: Controller::Human,
+                o: Controller::Remote,
+            },
+            GameMode::Online(Role::Join) => Controllers {
+                x: Controller::Remote,
+                o: Controller::Human,
+            },
+        }
+    }
+
+    pub fn get(&self, player: char) -> Controller {
+        if player == 'X' {
+            self.x
+        } else {
+            self.o
+        }
+    }
+
+    // The engine playing `player`, or None if a person plays it
+    pub fn engine(&self, player: char) -> Option<Engine> {
+        match self.get(player) {
+            Controller::Human | Controller::Remote => None,
+            Controller::Ai(engine) => Some(engine),
+        }
+    }
+}
+
+// The computer's move being searched for in the background
+#[derive(Resource)]
+pub struct AiThinking {
+    task: Task<()>,
+    choice: Arc<Mutex<Option<(usize, usize)>>>, // Filled in by the task when it is done
+    cancelled: Arc<AtomicBool>,
+    min_think: Timer,
+    pub player: char,
+}
+
+// Starts searching for the computer's move when it is its turn
+pub fn start_ai_turn(
+    mut commands: Commands,
+    game_state: Res<GameState>,
+    grid_config: Res<GridConfig>,
+    ai_settings: Res<AiSettings>,
+    controllers: Res<Controllers>,
+    ai_network: Option<Res<AiNetwork>>,
+    thinking: Option<Res<AiThinking>>,
+) {
+    let player = game_state.current_player;
+    let Some(engine) = controllers.engine(player) else {
+        return; // A person's turn
+    };
+    if thinking.is_some() || game_state.game_over {
+        return; // Skip if the game is over or the AI is already thinking
+    }
+
+    // The task gets its own copy of everything it needs
+    let grid = game_state.grid.clone();
+    let config = grid_config.clone();
+    let network = ai_network.map(|ai| ai.network.clone());
+    let choice = Arc::new(Mutex::new(None));
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let task = AsyncComputeTaskPool::get().spawn({
+        let choice = choice.clone();
+        let cancelled = cancelled.clone();
+        async move {
+            let cell = choose_move(
+                &grid,
+                player,
+                &config,
+                &engine,
+                network.as_ref(),
+                &cancelled,
+                &mut rand::thread_rng(),
+            );
+            *choice.lock().unwrap() = cell;
+        }
+    });
+
+    commands.insert_resource(AiThinking {
+        task,
+        choice,
+        cancelled,
+        min_think: Timer::from_seconds(ai_settings.min_think_seconds, TimerMode::Once),
+        player,
+    });
+}
+
+// Plays the computer's move once the search is done and the minimum think time has passed
+pub fn finish_ai_turn(
+    mut commands: Commands,
+    time: Res<Time>,
+    thinking: Option<ResMut<AiThinking>>,
+    mut game_state: ResMut<GameState>,
+    mut events: GameEvents,
+    grid_config: Res<GridConfig>,
+) {
+    let Some(mut thinking) = thinking else {
+        return;
+    };
+    if thinking.cancelled.load(Ordering::Relaxed) {
+        return; // Cancelled this frame; the resource goes once the commands are applied
+    }
+    thinking.min_think.tick(time.delta());
+    if !thinking.task.is_finished() || !thinking.min_think.finished() {
+        return;
+    }
+
+    commands.remove_resource::<AiThinking>();
+    let choice = thinking.choice.lock().unwrap().take();
+    if let Some((row, col)) = choice {
+        // The game cannot have moved on while the AI was thinking, but check anyway
+        let playable = !game_state.game_over
+            && game_state.current_player == thinking.player
+            && game_state.grid[row][col].is_none();
+        if playable {
+            // Place the mark and check for a win or draw after the AI's move
+            events.play(&mut game_state, row, col, &grid_config);
+        }
+    }
+}
+
+// Abandons the search when the game is reset, so its move never lands on the new board
+pub fn cancel_ai_turn(
+    mut commands: Commands,
+    mut resets: EventReader<GameReset>,
+    thinking: Option<Res<AiThinking>>,
+) {
+    if resets.iter().count() == 0 {
+        return;
+    }
+    if let Some(thinking) = thinking {
+        thinking.cancelled.store(true, Ordering::Relaxed);
+        // The search has no await points, so dropping its task does not stop it; the flag does
+        commands.remove_resource::<AiThinking>();
+    }
+}
+
+// Makes the computer move faster or slower with the - and = keys
+pub fn change_ai_pace(keys: Res<Input<KeyCode>>, mut ai_settings: ResMut<AiSettings>) {
+    let current = ai_settings.min_think_seconds;
+    let pace = if keys.any_just_pressed([KeyCode::Equals, KeyCode::NumpadAdd]) {
+        PACES.into_iter().rev().find(|&pace| pace < current)
+    } else if keys.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+        PACES.into_iter().find(|&pace| pace > current)
+    } else {
+        None
+    };
+    if let Some(pace) = pace {
+        ai_settings.min_think_seconds = pace;
+    }
+}
+
+// Picks a move for `player`, or None if the board is full. Setting `cancelled` makes a
+// running search give up early; its answer is then meaningless.
+pub fn choose_move(
+    grid: &[Vec<Option<char>>],
+    player: char,
+    config: &GridConfig,
+    engine: &Engine,
+    network: Option<&Network>,
+    cancelled: &AtomicBool,
+    rng: &mut impl Rng,
+) -> Option<(usize, usize)> {
+    let empty = empty_cells(grid);
+    if empty.is_empty() {
+        return None;
+    }
+
+    match engine.opponent {
+        Opponent::Random => empty.choose(rng).copied(),
+        Opponent::Network => match network {
+            // The network only knows standard full-length lines on the board it was trained for
+            Some(network)
+                if network.size() == config.size
+                    && config.win_length == config.size
+                    && config.variant == Variant::Standard =>
+            {
+                network.search(grid, player, NETWORK_SIMULATIONS, cancelled)
+            }
+            _ => minimax_move(grid, player, config, engine.difficulty, cancelled, rng),
+        },
+        Opponent::Minimax => {
+            if engine.difficulty == Difficulty::Easy && rng.gen_bool(EASY_RANDOM_CHANCE) {
+                empty.choose(rng).copied()
+            } else {
+                minimax_move(grid, player, config, engine.difficulty, cancelled, rng)
+            }
+        }
+    }
+}
+
+// Scores every empty cell with the shared minimax search and picks randomly among the best.
+// The search looks one move ahead on Easy, two on Medium and as far as it can afford on Hard.
+fn minimax_move(
+    grid: &[Vec<Option<char>>],
+    player: char,
+    config: &GridConfig,
+    difficulty: Difficulty,
+    cancelled: &AtomicBool,
+    rng: &mut impl Rng,
+) -> Option<(usize, usize)> {
+    let search = Search {
+        marks: ['X', 'O'],
+        win_length: config.win_length,
+        misere: config.variant == Variant::Misere,
+        cancelled: Some(cancelled),
+    };
+    let depth = match difficulty {
+        Difficulty::Easy => 1,
+        Difficulty::Medium => 2,
+        Difficulty::Hard => affordable_depth(empty_cells(grid).len()),
+    };
+    let side = if player == 'X' { 0 } else { 1 };
+    search.best_moves(grid, side, depth).choose(rng).copied()
+}
+
+fn empty_cells(grid: &[Vec<Option<char>>]) -> Vec<(usize, usize)> {
+    let size = grid.len();
+    (0..size)
+        .flat_map(|row| (0..size).map(move |col| (row, col)))
+        .filter(|&(row, col)| grid[row][col].is_none())
+        .collect()
+}
@@ -0,0 +1,241 @@
+// Placement, winning line and restart animations.
+// Animations are driven by the frame time, so they take as long at any frame rate.
+// Press F to finish every running animation at once, or start the game with
+// `--no-animations` to turn them off (useful for headless runs).
+
+use bevy::prelude::*;
+
+use crate::game::{
+    self, Board, GameEnded, GameReset, GameState, GridConfig, Mark, MovePlayed, CELL_SIZE,
+};
+use crate::theme::Theme;
+
+const PLACE_SECONDS: f32 = 0.25; // Time for a placed mark to grow to full size
+const STROKE_SECONDS: f32 = 0.5; // Time for the winning line to be drawn from end to end
+const FADE_SECONDS: f32 = 0.4; // Time for the board to fade out after a restart
+const STROKE_WIDTH: f32 = 10.0; // Thickness of the winning line
+
+#[derive(Resource)]
+pub struct Animations {
+    pub enabled: bool,
+}
+
+// Grows a newly placed mark from nothing to full size
+#[derive(Component)]
+pub struct Appear {
+    timer: Timer,
+}
+
+// Stroke drawn through the winning cells
+#[derive(Component)]
+pub struct WinLine {
+    start: Vec2,
+    end: Vec2,
+    timer: Timer,
+}
+
+// Fades a mark or the winning line out after a restart
+#[derive(Component)]
+pub struct FadeOut {
+    timer: Timer,
+}
+
+fn timer(seconds: f32) -> Timer {
+    Timer::from_seconds(seconds, TimerMode::Once)
+}
+
+// Eases from 0 to 1, quickly at first and then slowing down
+fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t) * (1.0 - t)
+}
+
+pub fn skip_animations(
+    keys: Res<Input<KeyCode>>,
+    mut appearing: Query<&mut Appear>,
+    mut lines: Query<&mut WinLine>,
+    mut fading: Query<&mut FadeOut>,
+) {
+    if keys.just_pressed(KeyCode::F) {
+        // Run every timer to the end; the animation systems then draw the final frame
+        let finish = |timer: &mut Timer| {
+            let remaining = timer.remaining();
+            timer.tick(remaining);
+        };
+        appearing
+            .iter_mut()
+            .for_each(|mut appear| finish(&mut appear.timer));
+        lines
+            .iter_mut()
+            .for_each(|mut line| finish(&mut line.timer));
+        fading
+            .iter_mut()
+            .for_each(|mut fade| finish(&mut fade.timer));
+    }
+}
+
+pub fn start_placement(
+    mut commands: Commands,
+    mut moves: EventReader<MovePlayed>,
+    animations: Res<Animations>,
+    mut query: Query<(Entity, &Mark, &mut Transform)>,
+) {
+    if !animations.enabled {
+        return;
+    }
+
+    for played in moves.iter() {
+        // Only the glyph of the new mark grows in
+        for (entity, _, mut transform) in query
+            .iter_mut()
+            .filter(|(_, mark, _)| mark.row == played.row && mark.col == played.col)
+        {
+            transform.scale = Vec3::ZERO;
+            commands.entity(entity).insert(Appear {
+                timer: timer(PLACE_SECONDS),
+            });
+        }
+    }
+}
+
+pub fn grow_marks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Appear, &mut Transform)>,
+) {
+    for (entity, mut appear, mut transform) in query.iter_mut() {
+        appear.timer.tick(time.delta());
+        transform.scale = Vec3::splat(ease_out(appear.timer.percent()));
+        if appear.timer.finished() {
+            commands.entity(entity).remove::<Appear>();
+        }
+    }
+}
+
+pub fn start_winning_line(
+    mut commands: Commands,
+    mut ended: EventReader<GameEnded>,
+    animations: Res<Animations>,
+    grid_config: Res<GridConfig>,
+    theme: Res<Theme>,
+    boards: Query<Entity, With<Board>>,
+) {
+    let Ok(board) = boards.get_single() else {
+        return;
+    };
+    // A draw has no line to draw
+    for ((start_row, start_col), (end_row, end_col)) in
+        ended.iter().filter_map(|result| result.line)
+    {
+        // Run the stroke a little past the centres of the end cells
+        let start = game::cell_position(start_row, start_col, grid_config.size);
+        let end = game::cell_position(end_row, end_col, grid_config.size);
+        let overhang = (end - start).normalize() * CELL_SIZE * 0.3;
+
+        let mut stroke = timer(STROKE_SECONDS);
+        if !animations.enabled {
+            stroke.tick(stroke.duration()); // Draw the whole line at once
+        }
+
+        // The line belongs to the board so it scales with it
+        commands.entity(board).with_children(|board| {
+            board.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: theme.winning_line,
+                        custom_size: Some(Vec2::new(0.0, STROKE_WIDTH)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(start.extend(3.0)), // Above the marks
+                    ..default()
+                },
+                WinLine {
+                    start: start - overhang,
+                    end: end + overhang,
+                    timer: stroke,
+                },
+            ));
+        });
+    }
+}
+
+pub fn draw_winning_line(
+    time: Res<Time>,
+    mut query: Query<(&mut WinLine, &mut Sprite, &mut Transform)>,
+) {
+    for (mut line, mut sprite, mut transform) in query.iter_mut() {
+        line.timer.tick(time.delta());
+
+        // Stretch the stroke from the start towards the end, centred on the drawn part
+        let direction = line.end - line.start;
+        let drawn = direction * ease_out(line.timer.percent());
+        sprite.custom_size = Some(Vec2::new(drawn.length(), STROKE_WIDTH));
+        transform.translation = (line.start + drawn / 2.0).extend(3.0);
+        transform.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x));
+    }
+}
+
+pub fn start_fade_out(
+    mut commands: Commands,
+    mut resets: EventReader<GameReset>,
+    animations: Res<Animations>,
+    game_state: Res<GameState>,
+    marks: Query<(Entity, &Mark, &Text), Without<FadeOut>>,
+    lines: Query<Entity, (With<WinLine>, Without<FadeOut>)>,
+) {
+    if resets.iter().count() == 0 {
+        return;
+    }
+
+    // After a restart the grid is empty while the old marks are still drawn
+    for entity in lines.iter() {
+        if animations.enabled {
+            commands.entity(entity).insert(FadeOut {
+                timer: timer(FADE_SECONDS),
+            });
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    if !animations.enabled {
+        return; // update_grid clears the marks straight away
+    }
+    for (entity, mark, text) in marks.iter() {
+        if game_state.grid[mark.row][mark.col].is_none() && !text.sections[0].value.is_empty() {
+            commands.entity(entity).insert(FadeOut {
+                timer: timer(FADE_SECONDS),
+            });
+        }
+    }
+}
+
+pub fn fade_out(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut FadeOut, Option<&mut Text>, Option<&mut Sprite>)>,
+) {
+    for (entity, mut fade, text, sprite) in query.iter_mut() {
+        fade.timer.tick(time.delta());
+        let alpha = 1.0 - fade.timer.percent();
+        let finished = fade.timer.finished();
+
+        if let Some(mut sprite) = sprite {
+            // The winning line goes away once it has faded, leaving the board's children
+            sprite.color.set_a(alpha);
+            if finished {
+                commands.entity(entity).despawn_recursive();
+            }
+        } else if let Some(mut text) = text {
+            // Marks are cleared and made opaque again, ready for the next game
+            let section = &mut text.sections[0];
+            section
+                .style
+                .color
+                .set_a(if finished { 1.0 } else { alpha });
+            if finished {
+                section.value.clear();
+                commands.entity(entity).remove::<FadeOut>();
+            }
+        }
+    }
+}
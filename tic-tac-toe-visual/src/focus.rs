@@ -0,0 +1,177 @@
+// Keyboard play. The arrow keys or WASD move a focus outline over the cells and Enter or Space
+// plays the focused cell; on a 3x3 board the digits 1-9 play a cell directly, laid out like a
+// numeric keypad (7 is top left). Moves are sent as cell selections, like mouse clicks.
+
+use bevy::prelude::*;
+
+use crate::game::{cell_position, CellSelected, GridConfig, CELL_SIZE};
+use crate::theme::Theme;
+
+const OUTLINE_WIDTH: f32 = 6.0; // In board units
+
+// The cell the keyboard points at. The outline appears once the keyboard is first used.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Focus {
+    pub row: usize,
+    pub col: usize,
+    pub shown: bool,
+}
+
+impl Focus {
+    // Starts on the centre cell, hidden
+    pub fn new(grid_size: usize) -> Self {
+        Focus {
+            row: grid_size / 2,
+            col: grid_size / 2,
+            shown: false,
+        }
+    }
+
+    // Moves by `rows` and `cols`, stopping at the edges of the board. The first move only shows
+    // where the focus is.
+    pub fn step(&mut self, rows: isize, cols: isize, grid_size: usize) {
+        let last = grid_size as isize - 1;
+        if self.shown {
+            self.row = (self.row as isize + rows).clamp(0, last) as usize;
+            self.col = (self.col as isize + cols).clamp(0, last) as usize;
+        }
+        self.shown = true;
+    }
+}
+
+#[derive(Component)]
+pub struct FocusOutline;
+
+// Spawns the outline, hidden, as a child of the board above the cells and grid lines
+pub fn spawn_focus_outline(board: &mut ChildBuilder, theme: &Theme) {
+    let half = (CELL_SIZE - OUTLINE_WIDTH) / 2.0;
+    board
+        .spawn((
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                transform: Transform::from_xyz(0.0, 0.0, 1.5),
+                ..default()
+            },
+            FocusOutline,
+        ))
+        .with_children(|outline| {
+            // Top, bottom, left and right edges
+            for (offset, size) in [
+                (Vec2::new(0.0, half), Vec2::new(CELL_SIZE, OUTLINE_WIDTH)),
+                (Vec2::new(0.0, -half), Vec2::new(CELL_SIZE, OUTLINE_WIDTH)),
+                (Vec2::new(-half, 0.0), Vec2::new(OUTLINE_WIDTH, CELL_SIZE)),
+                (Vec2::new(half, 0.0), Vec2::new(OUTLINE_WIDTH, CELL_SIZE)),
+            ] {
+                outline.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: theme.focus,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(offset.extend(0.0)),
+                    ..default()
+                });
+            }
+        });
+}
+
+pub fn move_focus(
+    keys: Res<Input<KeyCode>>,
+    grid_config: Res<GridConfig>,
+    mut focus: ResMut<Focus>,
+) {
+    // Row 0 is the bottom row, so up moves to a higher row
+    let (rows, cols): (isize, isize) = if keys.any_just_pressed([KeyCode::Up, KeyCode::W]) {
+        (1, 0)
+    } else if keys.any_just_pressed([KeyCode::Down, KeyCode::S]) {
+        (-1, 0)
+    } else if keys.any_just_pressed([KeyCode::Left, KeyCode::A]) {
+        (0, -1)
+    } else if keys.any_just_pressed([KeyCode::Right, KeyCode::D]) {
+        (0, 1)
+    } else {
+        return;
+    };
+    focus.step(rows, cols, grid_config.size);
+}
+
+// Plays the focused cell with Enter or Space, or a keypad digit's cell on a 3x3 board
+pub fn place_with_keys(
+    keys: Res<Input<KeyCode>>,
+    grid_config: Res<GridConfig>,
+    mut focus: ResMut<Focus>,
+    mut selections: EventWriter<CellSelected>,
+) {
+    if keys.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter, KeyCode::Space]) {
+        focus.shown = true;
+        selections.send(CellSelected {
+            row: focus.row,
+            col: focus.col,
+        });
+        return;
+    }
+
+    if grid_config.size != 3 {
+        return;
+    }
+    let digit = keys.get_just_pressed().find_map(|&key| keypad_digit(key));
+    if let Some(digit) = digit {
+        // 1 is bottom left and 9 top right, as on a keypad
+        let (row, col) = ((digit - 1) / 3, (digit - 1) % 3);
+        *focus = Focus {
+            row,
+            col,
+            shown: true,
+        };
+        selections.send(CellSelected { row, col });
+    }
+}
+
+// Keeps the outline on the focused cell, including on boards rebuilt by the settings
+pub fn show_focus(
+    focus: Res<Focus>,
+    grid_config: Res<GridConfig>,
+    mut outlines: Query<(&mut Transform, &mut Visibility), With<FocusOutline>>,
+) {
+    let position = cell_position(focus.row, focus.col, grid_config.size);
+    let visibility = if focus.shown {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    for (mut transform, mut shown) in outlines.iter_mut() {
+        if transform.translation.truncate() != position {
+            transform.translation = position.extend(transform.translation.z);
+        }
+        shown.set_if_neq(visibility);
+    }
+}
+
+// Keeps the focus on the board when the settings make the board smaller
+pub fn clamp_focus(grid_config: Res<GridConfig>, mut focus: ResMut<Focus>) {
+    if !grid_config.is_changed() {
+        return;
+    }
+    let last = grid_config.size - 1;
+    if focus.row > last || focus.col > last {
+        focus.row = focus.row.min(last);
+        focus.col = focus.col.min(last);
+    }
+}
+
+fn keypad_digit(key: KeyCode) -> Option<usize> {
+    let digit = match key {
+        KeyCode::Key1 | KeyCode::Numpad1 => 1,
+        KeyCode::Key2 | KeyCode::Numpad2 => 2,
+        KeyCode::Key3 | KeyCode::Numpad3 => 3,
+        KeyCode::Key4 | KeyCode::Numpad4 => 4,
+        KeyCode::Key5 | KeyCode::Numpad5 => 5,
+        KeyCode::Key6 | KeyCode::Numpad6 => 6,
+        KeyCode::Key7 | KeyCode::Numpad7 => 7,
+        KeyCode::Key8 | KeyCode::Numpad8 => 8,
+        KeyCode::Key9 | KeyCode::Numpad9 => 9,
+        _ => return None,
+    };
+    Some(digit)
+}
@@ -1,9 +1,23 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy::text::Text2dBundle; // Import for text rendering
-use rand::seq::IteratorRandom; // Import for random selection
 
-const CELL_SIZE: f32 = 100.0;
+use crate::ai::{AiThinking, Controller, Controllers};
+use crate::animation::FadeOut;
+use crate::focus;
+use crate::history::Review;
+use crate::layout::Layout;
+use crate::menu::AppState;
+use crate::nn::Network;
+use crate::online::Connection;
+use crate::theme::Theme;
+
+pub const CELL_SIZE: f32 = 100.0; // In board units; the board is scaled to fit the window
+const LINE_WIDTH: f32 = 4.0; // Thickness of the grid lines
+
+#[derive(Component)]
+pub struct Board; // Parent of the cells, grid lines and winning line
 
 #[derive(Component)]
 pub struct Cell {
@@ -11,19 +25,163 @@ pub struct Cell {
     col: usize,
 }
 
+// The X or O glyph drawn on top of a cell
+#[derive(Component)]
+pub struct Mark {
+    pub row: usize,
+    pub col: usize,
+}
+
+#[derive(Component)]
+pub struct GridLine; // Marker for the lines drawn between cells
+
 #[derive(Component)]
 pub struct WinnerText; // Marker for the winner banner text
 
 #[derive(Component)]
 pub struct ScoreText; // Marker for the score text
 
+#[derive(Component)]
+pub struct StatusText; // Marker for the status line: whose turn it is and what they are doing
+
 #[derive(Resource)]
 pub struct GameState {
-    grid: Vec<Vec<Option<char>>>, // Dynamic grid size
-    current_player: char,
-    game_over: bool, // Track if the game is over
-    winner: Option<char>, // Track the winner ('X', 'O', or None for a draw)
-    message_displayed: bool, // Track if the game-over message has been displayed
+    pub grid: Vec<Vec<Option<char>>>, // Dynamic grid size
+    pub current_player: char,
+    pub game_over: bool, // Track if the game is over
+    pub winner: Option<char>, // Track the winner ('X', 'O', or None for a draw)
+    pub first_player: char, // Player who moves first in every game
+    pub moves: Vec<(char, usize, usize)>, // Every move of this game in order: player, row, column
+}
+
+// A line of marks, from its first to its last cell
+pub type Line = ((usize, usize), (usize, usize));
+
+// Sent after a mark is placed
+#[derive(Event, Clone, Copy, Debug)]
+pub struct MovePlayed {
+    pub row: usize,
+    pub col: usize,
+}
+
+// Sent once when a move ends the game; `winner` is None for a draw
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct GameEnded {
+    pub winner: Option<char>,
+    pub line: Option<Line>, // The completed line, if any
+}
+
+// Asks to play a cell for the current player. Mouse clicks are turned into these, and tests
+// can send them directly.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CellSelected {
+    pub row: usize,
+    pub col: usize,
+}
+
+// Sent when the board is cleared for a new game
+#[derive(Event, Clone, Copy, Debug)]
+pub struct GameReset;
+
+// Moves and resets go through here so that every change to the game is announced
+#[derive(SystemParam)]
+pub struct GameEvents<'w> {
+    moves: EventWriter<'w, MovePlayed>,
+    ended: EventWriter<'w, GameEnded>,
+    resets: EventWriter<'w, GameReset>,
+}
+
+impl GameEvents<'_> {
+    pub fn play(
+        &mut self,
+        game_state: &mut GameState,
+        row: usize,
+        col: usize,
+        config: &GridConfig,
+    ) {
+        let result = game_state.play(row, col, config);
+        self.moves.send(MovePlayed { row, col });
+        if let Some(result) = result {
+            self.ended.send(result);
+        }
+    }
+
+    pub fn reset(&mut self, game_state: &mut GameState) {
+        game_state.reset();
+        self.resets.send(GameReset);
+    }
+
+    // Replaces the game, for example with one on a board of a different size
+    pub fn replace(&mut self, game_state: &mut GameState, new_state: GameState) {
+        *game_state = new_state;
+        self.resets.send(GameReset);
+    }
+}
+
+impl GameState {
+    pub fn new(config: &GridConfig) -> Self {
+        GameState {
+            grid: vec![vec![None; config.size]; config.size], // Dynamic grid size
+            current_player: config.first_player,
+            game_over: false,
+            winner: None,
+            first_player: config.first_player,
+            moves: Vec::new(),
+        }
+    }
+
+    // Clears the board for a new game, keeping the grid size
+    pub fn reset(&mut self) {
+        let size = self.grid.len();
+        self.grid = vec![vec![None; size]; size];
+        self.current_player = self.first_player;
+        self.game_over = false;
+        self.winner = None; // The marks are cleared by update_grid
+        self.moves.clear();
+    }
+
+    // The board as it was after the first `count` moves
+    pub fn board_after(&self, count: usize) -> Vec<Vec<Option<char>>> {
+        let size = self.grid.len();
+        let mut grid = vec![vec![None; size]; size];
+        for &(player, row, col) in self.moves.iter().take(count) {
+            grid[row][col] = Some(player);
+        }
+        grid
+    }
+
+    // Places the current player's mark, checks for a win or draw and passes the turn.
+    // Returns the result if the move ended the game.
+    pub fn play(&mut self, row: usize, col: usize, config: &GridConfig) -> Option<GameEnded> {
+        let player = self.current_player;
+        self.grid[row][col] = Some(player);
+        self.moves.push((player, row, col));
+        self.current_player = opponent(player);
+
+        let line = line_through(&self.grid, row, col, config.win_length);
+        if line.is_some() {
+            self.game_over = true;
+            self.winner = Some(match config.variant {
+                Variant::Standard => player,
+                Variant::Misere => opponent(player), // Completing a line loses
+            });
+        } else if is_draw(&self.grid) {
+            self.game_over = true;
+            self.winner = None;
+        } else {
+            return None;
+        }
+        Some(GameEnded {
+            winner: self.winner,
+            line,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Standard, // Completing a line wins
+    Misere,   // Completing a line loses
 }
 
 #[derive(Resource)]
@@ -33,9 +191,25 @@ pub struct Score {
     pub draws: u32,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct GridConfig {
-    pub size: usize, // Configurable grid size
+    pub size: usize,        // Configurable grid size
+    pub win_length: usize,  // Marks in a row needed to complete a line
+    pub variant: Variant,   // Whether completing a line wins or loses
+    pub first_player: char, // Player who moves first
+}
+
+#[derive(Resource)]
+pub struct AiNetwork {
+    pub network: Network, // Trained network used by the AI instead of random moves
+}
+
+pub fn spawn_camera(mut commands: Commands) {
+    // Spawn a 2D camera
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, 999.0)), // Ensure the camera is positioned correctly
+        ..default()
+    });
 }
 
 pub fn setup(
@@ -43,207 +217,322 @@ pub fn setup(
     windows: Query<&Window, With<PrimaryWindow>>,
     asset_server: Res<AssetServer>,
     grid_config: Res<GridConfig>, // Use the grid size from the configuration
+    theme: Res<Theme>,
 ) {
-    // Get the primary window dimensions
-    let window = windows.single();
-    let _window_width = window.width();
-    let _window_height = window.height();
+    // Fit the board and text to the primary window, or to a default-sized one when headless
+    let (width, height) = windows
+        .get_single()
+        .map(|window| (window.width(), window.height()))
+        .unwrap_or((1280.0, 720.0));
+    let layout = Layout::new(width, height, grid_config.size);
+    commands.insert_resource(layout);
 
-    // Spawn a 2D camera
-    commands.spawn(Camera2dBundle {
-        transform: Transform::from_translation(Vec3::new(0.0, 0.0, 999.0)), // Ensure the camera is positioned correctly
-        ..default()
-    });
+    let font = asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf");
+    spawn_board(&mut commands, &font, grid_config.size, layout.scale, &theme);
 
-    // Initialize game state
-    commands.insert_resource(GameState {
-        grid: vec![vec![None; grid_config.size]; grid_config.size], // Dynamic grid size
-        current_player: 'X',
-        game_over: false,
-        winner: None,
-        message_displayed: false, // Initialize the flag as false
-    });
+    // Add a text entity for the winner banner
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "", // Initially empty
+                TextStyle {
+                    font: font.clone(), // Ensure the font path is correct
+                    font_size: layout.banner_font,
+                    color: theme.text,
+                },
+            ),
+            transform: Transform::from_translation(Vec3::new(0.0, layout.banner_y, 1.0)), // Above the grid
+            ..default()
+        })
+        .insert(WinnerText);
 
-    // Initialize score tracking
-    commands.insert_resource(Score {
-        wins_x: 0,
-        wins_o: 0,
-        draws: 0,
-    });
+    // Add a text entity for the status line, where the banner goes once the game ends
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: layout.score_font,
+                    color: theme.text,
+                },
+            ),
+            transform: Transform::from_translation(Vec3::new(0.0, layout.banner_y, 1.0)),
+            ..default()
+        })
+        .insert(StatusText);
 
-    // Spawn grid cells, centered on the screen
-    let grid_size = grid_config.size;
+    // Add a text entity for the score display
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "Scores: Player X - 0, Player O - 0, Draws - 0", // Initial score
+                TextStyle {
+                    font,
+                    font_size: layout.score_font,
+                    color: theme.text,
+                },
+            ),
+            transform: Transform::from_translation(Vec3::new(0.0, layout.score_y, 1.0)), // Position below the grid
+            ..default()
+        })
+        .insert(ScoreText);
+}
+
+// Spawns the cells, their marks and the grid lines under a board centered on the screen
+pub fn spawn_board(
+    commands: &mut Commands,
+    font: &Handle<Font>,
+    grid_size: usize,
+    scale: f32,
+    theme: &Theme,
+) {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_scale(Vec3::splat(scale))),
+            Board,
+        ))
+        .with_children(|board| {
+            spawn_cells(board, font, grid_size, theme);
+            focus::spawn_focus_outline(board, theme);
+        });
+}
+
+fn spawn_cells(board: &mut ChildBuilder, font: &Handle<Font>, grid_size: usize, theme: &Theme) {
     let grid_width = grid_size as f32 * CELL_SIZE;
     let grid_height = grid_size as f32 * CELL_SIZE;
-    let start_x = -grid_width / 2.0 + CELL_SIZE / 2.0;
-    let start_y = -grid_height / 2.0 + CELL_SIZE / 2.0;
 
     for row in 0..grid_size {
         for col in 0..grid_size {
             // Alternate cell colors for better visibility
             let color = if (row + col) % 2 == 0 {
-                Color::rgb(0.9, 0.9, 0.9) // Light grey
+                theme.light_cell
             } else {
-                Color::rgb(0.6, 0.6, 0.6) // Dark grey
+                theme.dark_cell
             };
 
-            commands
+            board
                 .spawn(SpriteBundle {
                     sprite: Sprite {
                         color,
                         custom_size: Some(Vec2::new(CELL_SIZE, CELL_SIZE)),
                         ..default()
                     },
-                    transform: Transform::from_translation(Vec3::new(
-                        start_x + col as f32 * CELL_SIZE,
-                        start_y + row as f32 * CELL_SIZE,
-                        0.0,
-                    )),
+                    transform: Transform::from_translation(
+                        cell_position(row, col, grid_size).extend(0.0),
+                    ),
                     ..default()
                 })
-                .insert(Cell { row, col });
+                .insert(Cell { row, col })
+                .with_children(|parent| {
+                    // Glyph for the mark, empty until a player claims the cell
+                    parent
+                        .spawn(Text2dBundle {
+                            text: Text::from_section(
+                                "",
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: CELL_SIZE * 0.8,
+                                    color: theme.grid_line, // Replaced by the player's colour
+                                },
+                            ),
+                            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 2.0)),
+                            ..default()
+                        })
+                        .insert(Mark { row, col });
+                });
         }
     }
 
-    // Add a text entity for the winner banner
-    commands
-        .spawn(Text2dBundle {
-            text: Text::from_section(
-                "", // Initially empty
-                TextStyle {
-                    font: asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf"), // Ensure the font path is correct
-                    font_size: 50.0,
-                    color: Color::WHITE,
-                },
+    // Draw the grid lines between the cells, above the cells and below the marks
+    for i in 1..grid_size {
+        let offset = -grid_width / 2.0 + i as f32 * CELL_SIZE;
+        for (position, size) in [
+            // Vertical line, then horizontal line
+            (
+                Vec3::new(offset, 0.0, 1.0),
+                Vec2::new(LINE_WIDTH, grid_height),
             ),
-            transform: Transform::from_translation(Vec3::new(0.0, 250.0, 1.0)), // Move further above the grid
-            ..default()
-        })
-        .insert(WinnerText);
-
-    // Add a text entity for the score display
-    commands
-        .spawn(Text2dBundle {
-            text: Text::from_section(
-                "Scores: Player X - 0, Player O - 0, Draws - 0", // Initial score
-                TextStyle {
-                    font: asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf"),
-                    font_size: 30.0,
-                    color: Color::WHITE,
-                },
+            (
+                Vec3::new(0.0, offset, 1.0),
+                Vec2::new(grid_width, LINE_WIDTH),
             ),
-            transform: Transform::from_translation(Vec3::new(0.0, -250.0, 1.0)), // Position below the grid
-            ..default()
-        })
-        .insert(ScoreText);
+        ] {
+            board
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: theme.grid_line,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position),
+                    ..default()
+                })
+                .insert(GridLine);
+        }
+    }
 }
 
-pub fn handle_clicks(
-    mut commands: Commands,
+// Turns a left click on a cell into a cell selection
+pub fn read_mouse(
     windows: Query<&Window, With<PrimaryWindow>>,
     buttons: Res<Input<MouseButton>>,
-    mut game_state: ResMut<GameState>,
-    query: Query<(&Cell, &Transform)>,
+    query: Query<(&Cell, &GlobalTransform)>,
     camera_query: Query<(&Camera, &GlobalTransform)>, // Query the camera to adjust cursor position
+    mut selections: EventWriter<CellSelected>,
 ) {
-    if game_state.game_over {
-        return; // Prevent further moves if the game is over
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
     }
 
-    if buttons.just_pressed(MouseButton::Left) {
-        if let Ok(window) = windows.get_single() {
-            if let Some(cursor_pos) = window.cursor_position() {
-                // Get the camera and its global transform
-                if let Ok((camera, camera_transform)) = camera_query.get_single() {
-                    // Convert cursor position to world coordinates
-                    if let Some(world_pos) = camera.viewport_to_world(camera_transform, cursor_pos) {
-                        let world_pos = world_pos.origin.truncate(); // Get the 2D world position
-
-                        for (cell, transform) in query.iter() {
-                            let cell_pos = transform.translation.truncate();
-                            let half_size = CELL_SIZE / 2.0;
-
-                            // Check if the world position is within the bounds of the cell
-                            if world_pos.x > cell_pos.x - half_size
-                                && world_pos.x < cell_pos.x + half_size
-                                && world_pos.y > cell_pos.y - half_size
-                                && world_pos.y < cell_pos.y + half_size
-                            {
-                                if game_state.grid[cell.row][cell.col].is_none() {
-                                    game_state.grid[cell.row][cell.col] = Some(game_state.current_player);
-                                    game_state.current_player = if game_state.current_player == 'X' {
-                                        'O'
-                                    } else {
-                                        'X'
-                                    };
-
-                                    // Check for a win or draw after the move
-                                    if let Some(winner) = check_winner(&game_state.grid) {
-                                        game_state.game_over = true;
-                                        game_state.winner = Some(winner);
-                                    } else if is_draw(&game_state.grid) {
-                                        game_state.game_over = true;
-                                        game_state.winner = None;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    // Convert the cursor position to world coordinates
+    let world_pos = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor_pos| {
+            let (camera, camera_transform) = camera_query.get_single().ok()?;
+            camera.viewport_to_world(camera_transform, cursor_pos)
+        })
+        .map(|ray| ray.origin.truncate()); // Get the 2D world position
+    let Some(world_pos) = world_pos else {
+        return;
+    };
+
+    for (cell, transform) in query.iter() {
+        // Cursor position relative to the cell centre, in board units
+        let local_pos = transform
+            .affine()
+            .inverse()
+            .transform_point3(world_pos.extend(0.0));
+        let half_size = CELL_SIZE / 2.0;
+
+        // Check if the world position is within the bounds of the cell
+        if local_pos.x.abs() < half_size && local_pos.y.abs() < half_size {
+            selections.send(CellSelected {
+                row: cell.row,
+                col: cell.col,
+            });
+        }
+    }
+}
+
+// Plays each selected cell for the current player, ignoring cells that cannot be played
+pub fn handle_selection(
+    mut selections: EventReader<CellSelected>,
+    mut game_state: ResMut<GameState>,
+    mut events: GameEvents,
+    grid_config: Res<GridConfig>,
+    controllers: Res<Controllers>,
+    review: Res<Review>,
+    connection: Option<Res<Connection>>,
+) {
+    for &CellSelected { row, col } in selections.iter() {
+        if game_state.game_over || review.0.is_some() {
+            continue; // Prevent further moves if the game is over or an old position is shown
+        }
+        if connection.as_deref().is_some_and(|link| !link.is_connected()) {
+            continue; // An online game starts once the other player has joined
+        }
+        if controllers.get(game_state.current_player) != Controller::Human {
+            continue; // The computer's move, even while it is still thinking, or a remote player's
+        }
+        let empty = game_state
+            .grid
+            .get(row)
+            .and_then(|cells| cells.get(col))
+            .is_some_and(|cell| cell.is_none());
+        if empty {
+            // Place the mark and check for a win or draw
+            events.play(&mut game_state, row, col, &grid_config);
         }
     }
 }
 
 pub fn update_grid(
-    mut game_state: ResMut<GameState>, // Make game_state mutable to update the flag
-    mut score: ResMut<Score>, // Add score resource
-    mut query: Query<(&Cell, &mut Sprite)>,
+    game_state: Res<GameState>,
+    review: Res<Review>,
+    theme: Res<Theme>,
+    mut query: Query<(&Mark, &mut Text), Without<FadeOut>>, // Fading marks are cleared by the animation
 ) {
-    for (cell, mut sprite) in query.iter_mut() {
-        // Update the cell color based on the game state
-        if let Some(mark) = game_state.grid[cell.row][cell.col] {
-            sprite.color = if mark == 'X' {
-                Color::rgb(1.0, 0.0, 0.0) // Red for X
-            } else {
-                Color::rgb(0.0, 0.0, 1.0) // Blue for O
-            };
-        } else {
-            // Reset to default color if the cell is empty
-            sprite.color = if (cell.row + cell.col) % 2 == 0 {
-                Color::rgb(0.9, 0.9, 0.9) // Light grey
-            } else {
-                Color::rgb(0.6, 0.6, 0.6) // Dark grey
-            };
+    let grid = review.board(&game_state); // The live game, or the position being reviewed
+    for (mark, mut text) in query.iter_mut() {
+        // Draw the glyph of the player who claimed the cell, or nothing if it is empty.
+        // Text is only touched when it changes, so change detection only sees real changes.
+        let cell = grid[mark.row][mark.col];
+        let glyph = cell.map(String::from).unwrap_or_default();
+        if text.sections[0].value == glyph {
+            continue;
+        }
+        let section = &mut text.sections[0];
+        section.value = glyph;
+        if let Some(player) = cell {
+            section.style.color = theme.mark(player);
         }
     }
+}
 
-    if game_state.game_over && !game_state.message_displayed {
-        if let Some(winner) = game_state.winner {
-            if winner == 'X' {
-                score.wins_x += 1;
-            } else if winner == 'O' {
-                score.wins_o += 1;
-            }
-        } else {
-            score.draws += 1;
+// Counts each finished game exactly once
+pub fn record_result(mut ended: EventReader<GameEnded>, mut score: ResMut<Score>) {
+    for result in ended.iter() {
+        match result.winner {
+            Some('X') => score.wins_x += 1,
+            Some(_) => score.wins_o += 1,
+            None => score.draws += 1,
         }
-        game_state.message_displayed = true; // Set the flag to true
     }
 }
 
 pub fn update_winner_text(
-    game_state: Res<GameState>,
+    mut ended: EventReader<GameEnded>,
+    mut resets: EventReader<GameReset>,
     mut query: Query<&mut Text, With<WinnerText>>,
 ) {
-    if game_state.game_over && game_state.message_displayed {
-        if let Ok(mut text) = query.get_single_mut() {
-            if let Some(winner) = game_state.winner {
-                text.sections[0].value = format!("Player {} wins!", winner);
-            } else {
-                text.sections[0].value = "It's a draw!".to_string();
+    if let Ok(mut text) = query.get_single_mut() {
+        // Cleared while a game is in progress
+        if resets.iter().count() > 0 {
+            text.sections[0].value.clear();
+        }
+        for result in ended.iter() {
+            text.sections[0].value = match result.winner {
+                Some(winner) => format!("Player {} wins!", winner),
+                None => "It's a draw!".to_string(),
+            };
+        }
+    }
+}
+
+// Shows the move number and who is to play: a person at this computer, the computer, which may
+// be thinking, or a remote player the game is waiting for. An online game that is still
+// connecting or has been cut off says so instead. Empty once the banner has the result.
+pub fn update_status_text(
+    game_state: Res<GameState>,
+    controllers: Res<Controllers>,
+    thinking: Option<Res<AiThinking>>,
+    connection: Option<Res<Connection>>,
+    mut query: Query<&mut Text, With<StatusText>>,
+) {
+    let Ok(mut text) = query.get_single_mut() else {
+        return;
+    };
+    let value = if game_state.game_over {
+        String::new()
+    } else if let Some(status) = connection.as_deref().and_then(Connection::status) {
+        status
+    } else {
+        let player = game_state.current_player;
+        let turn = format!("Move {}: Player {}", game_state.moves.len() + 1, player);
+        match controllers.get(player) {
+            Controller::Human => format!("{} (human) to play", turn),
+            Controller::Ai(_) if thinking.is_some() => {
+                format!("{} (AI) is thinking\u{2026}", turn)
             }
+            Controller::Ai(_) => format!("{} (AI) to play", turn),
+            Controller::Remote => format!("{} (online): waiting for their move\u{2026}", turn),
         }
+    };
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
     }
 }
 
@@ -262,119 +551,214 @@ pub fn update_score_text(
 pub fn handle_restart(
     keys: Res<Input<KeyCode>>,
     mut game_state: ResMut<GameState>,
-    mut winner_text_query: Query<&mut Text, With<WinnerText>>,
-    mut query: Query<(&Cell, &mut Sprite)>,
+    mut events: GameEvents,
+    mut next_state: ResMut<NextState<AppState>>,
 ) {
     if keys.just_pressed(KeyCode::R) {
-        // Reset the game state
-        game_state.grid = vec![vec![None; game_state.grid.len()]; game_state.grid.len()]; // Dynamic grid size
-        game_state.current_player = 'X';
-        game_state.game_over = false;
-        game_state.winner = None;
-        game_state.message_displayed = false;
-
-        // Reset the grid visuals
-        for (cell, mut sprite) in query.iter_mut() {
-            sprite.color = if (cell.row + cell.col) % 2 == 0 {
-                Color::rgb(0.9, 0.9, 0.9) // Light grey
-            } else {
-                Color::rgb(0.6, 0.6, 0.6) // Dark grey
-            };
-        }
-
-        // Clear the winner banner text
-        if let Ok(mut text) = winner_text_query.get_single_mut() {
-            text.sections[0].value = "".to_string();
-        }
+        // Reset the game state; the banner is cleared by update_winner_text
+        events.reset(&mut game_state);
+        next_state.set(AppState::Playing);
     }
 }
 
-pub fn handle_ai_turn(
-    mut game_state: ResMut<GameState>,
-    mut query: Query<&Cell>,
-) {
-    if game_state.game_over || game_state.current_player != 'O' {
-        return; // Skip if the game is over or it's not AI's turn
-    }
-
-    // Find all empty cells
-    let empty_cells: Vec<&Cell> = query
-        .iter()
-        .filter(|cell| game_state.grid[cell.row][cell.col].is_none())
-        .collect();
-
-    // Randomly select an empty cell
-    if let Some(cell) = empty_cells.iter().choose(&mut rand::thread_rng()) {
-        game_state.grid[cell.row][cell.col] = Some('O');
-        game_state.current_player = 'X'; // Switch back to the human player
-
-        // Check for a win or draw after the AI's move
-        if let Some(winner) = check_winner(&game_state.grid) {
-            game_state.game_over = true;
-            game_state.winner = Some(winner);
-        } else if is_draw(&game_state.grid) {
-            game_state.game_over = true;
-            game_state.winner = None;
-        }
-    }
+// Helper function to check for a winner, counting only lines that span the whole grid
+pub fn check_winner(grid: &[Vec<Option<char>>]) -> Option<char> {
+    winning_line(grid, grid.len()).and_then(|((row, col), _)| grid[row][col])
 }
 
-// Helper function to check for a winner
-pub fn check_winner(grid: &Vec<Vec<Option<char>>>) -> Option<char> {
+// Helper function to find the first and last cells of a line of at least `length` marks
+pub fn winning_line(grid: &[Vec<Option<char>>], length: usize) -> Option<Line> {
     let size = grid.len();
+    (0..size)
+        .flat_map(|row| (0..size).map(move |col| (row, col)))
+        .find_map(|(row, col)| line_through(grid, row, col, length))
+}
 
-    // Check rows and columns
-    for i in 0..size {
-        if grid[i][0].is_some() && grid[i].iter().all(|&cell| cell == grid[i][0]) {
-            return grid[i][0];
-        }
-        if grid[0][i].is_some() && (0..size).all(|j| grid[j][i] == grid[0][i]) {
-            return grid[0][i];
+// Helper function to find a line of at least `length` identical marks through a cell.
+// Only lines through that cell are examined, so it is cheap to call after each move.
+pub fn line_through(
+    grid: &[Vec<Option<char>>],
+    row: usize,
+    col: usize,
+    length: usize,
+) -> Option<Line> {
+    let mark = grid[row][col]?;
+    let size = grid.len() as isize;
+
+    // Walks from the cell in one direction while the marks match, returning the last match
+    let walk = |dr: isize, dc: isize| {
+        let (mut r, mut c) = (row as isize, col as isize);
+        let mut count = 0;
+        while (0..size).contains(&(r + dr))
+            && (0..size).contains(&(c + dc))
+            && grid[(r + dr) as usize][(c + dc) as usize] == Some(mark)
+        {
+            r += dr;
+            c += dc;
+            count += 1;
         }
-    }
+        ((r as usize, c as usize), count)
+    };
+
+    // Rows, columns and both diagonals
+    [(0, 1), (1, 0), (1, 1), (1, -1)]
+        .into_iter()
+        .find_map(|(dr, dc)| {
+            let (start, behind) = walk(-dr, -dc);
+            let (end, ahead) = walk(dr, dc);
+            (behind + ahead + 1 >= length).then_some((start, end))
+        })
+}
 
-    // Check diagonals
-    if grid[0][0].is_some() && (0..size).all(|i| grid[i][i] == grid[0][0]) {
-        return grid[0][0];
-    }
-    if grid[0][size - 1].is_some() && (0..size).all(|i| grid[i][size - 1 - i] == grid[0][size - 1]) {
-        return grid[0][size - 1];
+// Helper function to get the other player
+pub fn opponent(player: char) -> char {
+    if player == 'X' {
+        'O'
+    } else {
+        'X'
     }
+}
 
-    None // No winner
+// Helper function to get the centre of a cell in board units; row 0 is the bottom row
+pub fn cell_position(row: usize, col: usize, size: usize) -> Vec2 {
+    let start = -(size as f32) * CELL_SIZE / 2.0 + CELL_SIZE / 2.0;
+    Vec2::new(
+        start + col as f32 * CELL_SIZE,
+        start + row as f32 * CELL_SIZE,
+    )
 }
 
 // Helper function to check for a draw
-pub fn is_draw(grid: &Vec<Vec<Option<char>>>) -> bool {
+pub fn is_draw(grid: &[Vec<Option<char>>]) -> bool {
     grid.iter().all(|row| row.iter().all(|&cell| cell.is_some()))
 }
 
-pub fn log_game_record(
-    mut game_state: ResMut<GameState>, // Make game_state mutable to reset the flag
-    mut score: ResMut<Score>,         // Make score mutable to ensure it updates only once
-) {
-    if game_state.game_over && !game_state.message_displayed {
+pub fn log_game_record(mut ended: EventReader<GameEnded>, score: Res<Score>) {
+    for result in ended.iter() {
         // Log the game result
         println!("Game Over!");
-        if let Some(winner) = game_state.winner {
+        if let Some(winner) = result.winner {
             println!("Winner: Player {}", winner);
-            if winner == 'X' {
-                score.wins_x += 1; // Increment score for Player X
-            } else if winner == 'O' {
-                score.wins_o += 1; // Increment score for Player O
-            }
         } else {
             println!("It's a draw!");
-            score.draws += 1; // Increment draw count
         }
 
-        // Log the updated scores
+        // Log the updated scores; record_result runs first
         println!(
             "Scores: Player X - {}, Player O - {}, Draws - {}",
             score.wins_x, score.wins_o, score.draws
         );
+    }
+}
 
-        // Set the message_displayed flag to prevent repeated logging
-        game_state.message_displayed = true;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Moves still to be played, one per frame
+    #[derive(Resource)]
+    struct Script(Vec<(usize, usize)>);
+
+    fn play_script(
+        mut script: ResMut<Script>,
+        mut game_state: ResMut<GameState>,
+        mut events: GameEvents,
+        grid_config: Res<GridConfig>,
+    ) {
+        if game_state.game_over {
+            return;
+        }
+        if !script.0.is_empty() {
+            let (row, col) = script.0.remove(0);
+            events.play(&mut game_state, row, col, &grid_config);
+        }
+    }
+
+    // Resets the game once it is over and there are more moves to play
+    fn restart_when_over(
+        script: Res<Script>,
+        mut game_state: ResMut<GameState>,
+        mut events: GameEvents,
+    ) {
+        if game_state.game_over && !script.0.is_empty() {
+            events.reset(&mut game_state);
+        }
+    }
+
+    // A headless app with the scoring and logging systems, without a window or rendering
+    fn app(moves: Vec<(usize, usize)>) -> App {
+        let config = GridConfig {
+            size: 3,
+            win_length: 3,
+            variant: Variant::Standard,
+            first_player: 'X',
+        };
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<MovePlayed>()
+            .add_event::<GameEnded>()
+            .add_event::<GameReset>()
+            .insert_resource(GameState::new(&config))
+            .insert_resource(config)
+            .insert_resource(Score {
+                wins_x: 0,
+                wins_o: 0,
+                draws: 0,
+            })
+            .insert_resource(Script(moves))
+            .add_systems(
+                Update,
+                (
+                    play_script,
+                    restart_when_over.after(play_script),
+                    record_result,
+                    log_game_record.after(record_result),
+                    update_winner_text,
+                ),
+            );
+        app
+    }
+
+    fn score(app: &App) -> (u32, u32, u32) {
+        let score = app.world.resource::<Score>();
+        (score.wins_x, score.wins_o, score.draws)
+    }
+
+    #[test]
+    fn a_win_is_counted_once() {
+        // X takes the bottom row
+        let mut app = app(vec![(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]);
+        for _ in 0..20 {
+            app.update(); // Frames after the game ends must not count it again
+        }
+        assert!(app.world.resource::<GameState>().game_over);
+        assert_eq!(score(&app), (1, 0, 0));
+    }
+
+    #[test]
+    fn every_game_of_a_session_is_counted_once() {
+        let mut app = app(vec![
+            // O wins the middle column
+            (0, 0),
+            (0, 1),
+            (2, 2),
+            (1, 1),
+            (2, 0),
+            (2, 1),
+            // A draw
+            (0, 0),
+            (1, 1),
+            (2, 2),
+            (0, 1),
+            (2, 1),
+            (2, 0),
+            (0, 2),
+            (1, 2),
+            (1, 0),
+        ]);
+        for _ in 0..40 {
+            app.update();
+        }
+        assert_eq!(score(&app), (0, 1, 1));
     }
 }
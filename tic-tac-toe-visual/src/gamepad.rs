@@ -0,0 +1,147 @@
+// Gamepad play. The D-pad or left stick moves the same cell cursor as the keyboard, A (the south
+// button) plays the focused cell, Start restarts and Select opens or closes the pause menu. With
+// two gamepads the first one connected plays X and the second plays O; a single gamepad plays
+// whichever side is to move.
+
+use bevy::input::gamepad::GamepadConnectionEvent;
+use bevy::prelude::*;
+
+use crate::focus::Focus;
+use crate::game::{CellSelected, GameEvents, GameState, GridConfig};
+use crate::menu::AppState;
+
+const STICK_THRESHOLD: f32 = 0.5; // How far the stick must be pushed to move the cursor
+
+// Connected gamepads in the order they were connected, and where each stick last pointed
+#[derive(Resource, Default)]
+pub struct GamepadSeats {
+    pads: Vec<Gamepad>,
+    sticks: Vec<IVec2>, // One per pad; the cursor moves when a stick leaves the centre
+}
+
+impl GamepadSeats {
+    // The gamepad that plays `player`, if any
+    pub fn pad_for(&self, player: char) -> Option<Gamepad> {
+        match self.pads.as_slice() {
+            [] => None,
+            [pad] => Some(*pad),
+            [x, o, ..] => Some(if player == 'X' { *x } else { *o }),
+        }
+    }
+}
+
+pub fn track_gamepads(
+    mut connections: EventReader<GamepadConnectionEvent>,
+    mut seats: ResMut<GamepadSeats>,
+) {
+    for event in connections.iter() {
+        let known = seats.pads.iter().position(|&pad| pad == event.gamepad);
+        match (event.connected(), known) {
+            (true, None) => {
+                seats.pads.push(event.gamepad);
+                seats.sticks.push(IVec2::ZERO);
+            }
+            (false, Some(index)) => {
+                seats.pads.remove(index);
+                seats.sticks.remove(index);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Moves the cursor and plays the focused cell for the gamepad of the player to move
+pub fn gamepad_cursor(
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut seats: ResMut<GamepadSeats>,
+    game_state: Res<GameState>,
+    grid_config: Res<GridConfig>,
+    mut focus: ResMut<Focus>,
+    mut selections: EventWriter<CellSelected>,
+) {
+    let active = seats.pad_for(game_state.current_player);
+
+    // Every stick is tracked, so a stick held while waiting does not move the cursor later
+    let mut moved = IVec2::ZERO;
+    let GamepadSeats { pads, sticks } = &mut *seats;
+    for (&pad, last) in pads.iter().zip(sticks.iter_mut()) {
+        let stick = stick_direction(&axes, pad);
+        if Some(pad) == active && stick != *last && stick != IVec2::ZERO {
+            moved = stick;
+        }
+        *last = stick;
+    }
+    let Some(pad) = active else {
+        return;
+    };
+
+    let pressed = |button_type| buttons.just_pressed(GamepadButton::new(pad, button_type));
+    if pressed(GamepadButtonType::DPadUp) {
+        moved = IVec2::Y; // Row 0 is the bottom row, so up moves to a higher row
+    } else if pressed(GamepadButtonType::DPadDown) {
+        moved = IVec2::NEG_Y;
+    } else if pressed(GamepadButtonType::DPadLeft) {
+        moved = IVec2::NEG_X;
+    } else if pressed(GamepadButtonType::DPadRight) {
+        moved = IVec2::X;
+    }
+    if moved != IVec2::ZERO {
+        focus.step(moved.y as isize, moved.x as isize, grid_config.size);
+    }
+
+    if pressed(GamepadButtonType::South) {
+        focus.shown = true;
+        selections.send(CellSelected {
+            row: focus.row,
+            col: focus.col,
+        });
+    }
+}
+
+// Start restarts the game and Select pauses or resumes it, from any gamepad
+pub fn gamepad_buttons(
+    buttons: Res<Input<GamepadButton>>,
+    seats: Res<GamepadSeats>,
+    state: Res<State<AppState>>,
+    mut game_state: ResMut<GameState>,
+    mut events: GameEvents,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let pressed = |button_type| {
+        seats
+            .pads
+            .iter()
+            .any(|&pad| buttons.just_pressed(GamepadButton::new(pad, button_type)))
+    };
+
+    match state.get() {
+        AppState::Playing | AppState::GameOver if pressed(GamepadButtonType::Start) => {
+            // Reset the game state; the banner is cleared by update_winner_text
+            events.reset(&mut game_state);
+            next_state.set(AppState::Playing);
+        }
+        AppState::Playing if pressed(GamepadButtonType::Select) => next_state.set(AppState::Paused),
+        AppState::Paused if pressed(GamepadButtonType::Select) => next_state.set(AppState::Playing),
+        _ => {}
+    }
+}
+
+// Which way the left stick points: one step along its stronger axis, or zero near the centre
+fn stick_direction(axes: &Axis<GamepadAxis>, pad: Gamepad) -> IVec2 {
+    let axis = |axis_type| {
+        axes.get(GamepadAxis::new(pad, axis_type))
+            .unwrap_or_default()
+    };
+    let (x, y) = (
+        axis(GamepadAxisType::LeftStickX),
+        axis(GamepadAxisType::LeftStickY),
+    );
+    if x.abs().max(y.abs()) < STICK_THRESHOLD {
+        IVec2::ZERO
+    } else if x.abs() > y.abs() {
+        IVec2::new(x.signum() as i32, 0)
+    } else {
+        IVec2::new(0, y.signum() as i32)
+    }
+}
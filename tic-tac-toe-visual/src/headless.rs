@@ -0,0 +1,228 @@
+// Runs the game under `MinimalPlugins`, without a window or GPU, for tests and tools.
+// Input comes from injected cell selections, key presses and gamepad events instead of the mouse,
+// keyboard and gamepads, and frames are stepped by hand. Animations are turned off so results
+// show up straight away.
+
+use bevy::input::gamepad::{
+    gamepad_axis_event_system, gamepad_button_event_system, gamepad_connection_system,
+    gamepad_event_system, GamepadAxisChangedEvent, GamepadButtonChangedEvent, GamepadConnection,
+    GamepadConnectionEvent, GamepadEvent, GamepadInfo, GamepadSettings,
+};
+use bevy::prelude::*;
+use bevy::window::WindowResized;
+use std::thread;
+use std::time::Duration;
+
+use crate::ai::AiThinking;
+use crate::game::{CellSelected, GameState, Mark, Score, StatusText, WinnerText};
+use crate::history::Review;
+use crate::menu::{AppState, GameMode, MenuButton};
+use crate::plugin::{TicTacToeConfig, TicTacToePlugin};
+use crate::settings::Settings;
+
+pub struct Harness {
+    pub app: App,
+}
+
+impl Harness {
+    // Builds the app and runs the first frame, which leaves it on the main menu
+    pub fn new(config: TicTacToeConfig) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .add_asset::<Font>()
+            .add_event::<WindowResized>() // Normally added by the window plugin
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            // The gamepad half of the input plugin; all of it would clear the injected key presses
+            .add_event::<GamepadEvent>()
+            .add_event::<GamepadConnectionEvent>()
+            .add_event::<GamepadButtonChangedEvent>()
+            .add_event::<GamepadAxisChangedEvent>()
+            .init_resource::<GamepadSettings>()
+            .init_resource::<Gamepads>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Axis<GamepadButton>>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .add_systems(
+                PreUpdate,
+                (
+                    gamepad_event_system,
+                    gamepad_connection_system.after(gamepad_event_system),
+                    (gamepad_button_event_system, gamepad_axis_event_system)
+                        .after(gamepad_event_system)
+                        .after(gamepad_connection_system),
+                ),
+            )
+            .add_plugins(TicTacToePlugin {
+                config: TicTacToeConfig {
+                    animations: false,
+                    spawn_camera: false,
+                    ..config
+                },
+            });
+        app.update();
+        Harness { app }
+    }
+
+    // Leaves the main menu for a game in `mode` by pressing its button
+    pub fn start(&mut self, mode: GameMode) {
+        let mut buttons = self.app.world.query::<(Entity, &MenuButton)>();
+        let button = buttons
+            .iter(&self.app.world)
+            .find(|(_, button)| matches!(button, MenuButton::Play(play) if *play == mode))
+            .map(|(entity, _)| entity)
+            .expect("the main menu has a button for every mode");
+        self.app
+            .world
+            .entity_mut(button)
+            .insert(Interaction::Pressed);
+        self.step(2); // The press is handled, then the game screen opens
+    }
+
+    // Opens the settings screen, changes them and goes back to the main menu, which applies them
+    pub fn change_settings(&mut self, change: impl FnOnce(&mut Settings)) {
+        self.app
+            .world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Settings);
+        self.step(1);
+        change(&mut self.app.world.resource_mut::<Settings>());
+        self.app
+            .world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::MainMenu);
+        self.step(1);
+    }
+
+    // Selects a cell, as a click on it would, and runs a frame
+    pub fn select(&mut self, row: usize, col: usize) {
+        self.app.world.send_event(CellSelected { row, col });
+        self.step(1);
+    }
+
+    // Holds a key down for one frame
+    pub fn press(&mut self, key: KeyCode) {
+        self.app.world.resource_mut::<Input<KeyCode>>().press(key);
+        self.step(1);
+        // Without the input plugin nothing else releases the key
+        let mut keys = self.app.world.resource_mut::<Input<KeyCode>>();
+        keys.release(key);
+        keys.clear();
+    }
+
+    // Plugs in gamepad `id`, as the gamepad backend reports it
+    pub fn connect_gamepad(&mut self, id: usize) {
+        let info = GamepadInfo {
+            name: format!("Test gamepad {}", id),
+        };
+        self.app
+            .world
+            .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+                Gamepad::new(id),
+                GamepadConnection::Connected(info),
+            )));
+        self.step(1);
+    }
+
+    // Holds a gamepad button down for one frame
+    pub fn press_gamepad(&mut self, id: usize, button: GamepadButtonType) {
+        for value in [1.0, 0.0] {
+            self.app
+                .world
+                .send_event(GamepadEvent::Button(GamepadButtonChangedEvent::new(
+                    Gamepad::new(id),
+                    button,
+                    value,
+                )));
+            self.step(1);
+        }
+    }
+
+    // Moves the left stick of gamepad `id` and runs a frame
+    pub fn move_stick(&mut self, id: usize, x: f32, y: f32) {
+        for (axis, value) in [
+            (GamepadAxisType::LeftStickX, x),
+            (GamepadAxisType::LeftStickY, y),
+        ] {
+            self.app
+                .world
+                .send_event(GamepadEvent::Axis(GamepadAxisChangedEvent::new(
+                    Gamepad::new(id),
+                    axis,
+                    value,
+                )));
+        }
+        self.step(1);
+    }
+
+    pub fn step(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    // Runs frames until the computer has played its move, or gives up after a few seconds
+    pub fn wait_for_ai(&mut self) {
+        for _ in 0..500 {
+            self.step(1);
+            if !self.ai_thinking() {
+                self.step(1); // The status line sees the search end a frame later
+                return;
+            }
+            thread::sleep(Duration::from_millis(10)); // The search runs on another thread
+        }
+        panic!("the computer did not finish its move");
+    }
+
+    pub fn ai_thinking(&self) -> bool {
+        self.app.world.contains_resource::<AiThinking>()
+    }
+
+    // Shows the board after `moves` moves, as clicking the move history does, or the live
+    // game for None
+    pub fn review(&mut self, moves: Option<usize>) {
+        self.app.insert_resource(Review(moves));
+        self.step(1);
+    }
+
+    // The board as drawn, which differs from the game state while reviewing
+    pub fn shown_board(&mut self) -> Vec<Vec<Option<char>>> {
+        let size = self.game_state().grid.len();
+        let mut grid = vec![vec![None; size]; size];
+        let mut query = self.app.world.query::<(&Mark, &Text)>();
+        for (mark, text) in query.iter(&self.app.world) {
+            grid[mark.row][mark.col] = text.sections[0].value.chars().next();
+        }
+        grid
+    }
+
+    pub fn game_state(&self) -> &GameState {
+        self.app.world.resource::<GameState>()
+    }
+
+    pub fn score(&self) -> &Score {
+        self.app.world.resource::<Score>()
+    }
+
+    pub fn app_state(&self) -> AppState {
+        *self.app.world.resource::<State<AppState>>().get()
+    }
+
+    // The text of the winner banner
+    pub fn winner_text(&mut self) -> String {
+        self.text::<WinnerText>()
+    }
+
+    // The text of the status line above the board
+    pub fn status_text(&mut self) -> String {
+        self.text::<StatusText>()
+    }
+
+    fn text<T: Component>(&mut self) -> String {
+        let mut query = self.app.world.query_filtered::<&Text, With<T>>();
+        query
+            .get_single(&self.app.world)
+            .map(|text| text.sections[0].value.clone())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,187 @@
+// The move history panel beside the board. It lists every move of the current game; clicking a
+// move shows the board as it was after it, read-only, until "Return to live" is pressed. The
+// reviewed position is drawn from `GameState::moves`, so the live game is never touched.
+
+use bevy::prelude::*;
+use tic_tac_toe_common::notation;
+
+use crate::animation::WinLine;
+use crate::game::{GameReset, GameState};
+use crate::menu;
+use crate::theme::Theme;
+
+pub const PANEL_WIDTH: f32 = 220.0; // Window pixels kept free for the panel right of the board
+
+// Which position the board shows: None for the live game, or the number of moves played
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Review(pub Option<usize>);
+
+impl Review {
+    // The board to draw: the live grid, or the reviewed position
+    pub fn board(&self, game_state: &GameState) -> Vec<Vec<Option<char>>> {
+        match self.0 {
+            Some(count) => game_state.board_after(count),
+            None => game_state.grid.clone(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct HistoryList; // Holds one button per move
+
+// Shows the board after this many moves
+#[derive(Component, Clone, Copy)]
+pub struct HistoryEntry(usize);
+
+#[derive(Component)]
+pub struct ReturnToLive;
+
+#[derive(Component)]
+pub struct ReviewText; // Says which move is shown, or nothing when live
+
+// Formats a cell in algebraic notation: column letter, then row number from the bottom
+pub fn notation(row: usize, col: usize) -> String {
+    notation::cell_name(col, row + 1)
+}
+
+pub fn spawn_history_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+) {
+    let font = asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf");
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(0.0),
+                top: Val::Px(0.0),
+                width: Val::Px(PANEL_WIDTH),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(10.0)),
+                overflow: Overflow::clip_y(), // Long games run off the bottom
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|panel| {
+            panel.spawn(menu::label("Moves", 28.0, &font, &theme));
+            panel.spawn((menu::label("", 18.0, &font, &theme), ReviewText));
+            menu::spawn_button(panel, &font, &theme, "Return to live", ReturnToLive)
+                .insert((menu::button_style(PANEL_WIDTH - 20.0), Visibility::Hidden));
+            panel.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+                HistoryList,
+            ));
+        });
+}
+
+// Rebuilds the list when moves are played or the board is cleared
+pub fn update_history_list(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_state: Res<GameState>,
+    theme: Res<Theme>,
+    lists: Query<(Entity, Option<&Children>), With<HistoryList>>,
+) {
+    let Ok((list, entries)) = lists.get_single() else {
+        return;
+    };
+    let shown = entries.map_or(0, |entries| entries.len());
+    if shown == game_state.moves.len() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf");
+    commands.entity(list).despawn_descendants();
+    commands.entity(list).with_children(|list| {
+        for (index, &(player, row, col)) in game_state.moves.iter().enumerate() {
+            let value = format!("{}. {} {}", index + 1, player, notation(row, col));
+            menu::spawn_button(list, &font, &theme, &value, HistoryEntry(index + 1)).insert((
+                Style {
+                    width: Val::Px(90.0),
+                    height: Val::Px(30.0),
+                    margin: UiRect::all(Val::Px(4.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(theme.button),
+            ));
+        }
+    });
+}
+
+pub fn handle_history_buttons(
+    entries: Query<(&Interaction, &HistoryEntry), Changed<Interaction>>,
+    returns: Query<&Interaction, (Changed<Interaction>, With<ReturnToLive>)>,
+    mut resets: EventReader<GameReset>,
+    mut review: ResMut<Review>,
+) {
+    for (interaction, entry) in entries.iter() {
+        if *interaction == Interaction::Pressed {
+            review.0 = Some(entry.0);
+        }
+    }
+    let back = returns
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    // A new game has no moves to review
+    if (back || resets.iter().count() > 0) && review.0.is_some() {
+        review.0 = None;
+    }
+}
+
+// Marks the reviewed move, shows the return button and hides the winning line of the live game.
+// Runs after the buttons are highlighted, so hovering does not hide which move is shown.
+#[allow(clippy::type_complexity)]
+pub fn show_review(
+    review: Res<Review>,
+    game_state: Res<GameState>,
+    theme: Res<Theme>,
+    mut entries: Query<(&HistoryEntry, &mut BackgroundColor)>,
+    mut returns: Query<&mut Visibility, (With<ReturnToLive>, Without<WinLine>)>,
+    mut lines: Query<&mut Visibility, (With<WinLine>, Without<ReturnToLive>)>,
+    mut texts: Query<&mut Text, With<ReviewText>>,
+) {
+    for (entry, mut background) in entries.iter_mut() {
+        if review.0 == Some(entry.0) {
+            *background = theme.button_selected.into();
+        } else if review.is_changed() {
+            *background = theme.button.into();
+        }
+    }
+
+    // New winning lines appear when the live game ends during a review, so these are set
+    // every frame
+    let (button, line) = match review.0 {
+        Some(_) => (Visibility::Inherited, Visibility::Hidden),
+        None => (Visibility::Hidden, Visibility::Inherited),
+    };
+    for mut visibility in returns.iter_mut() {
+        visibility.set_if_neq(button);
+    }
+    for mut visibility in lines.iter_mut() {
+        visibility.set_if_neq(line);
+    }
+
+    let value = match review.0 {
+        Some(count) => format!("Reviewing move {} of {}", count, game_state.moves.len()),
+        None => String::new(),
+    };
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// Fits the board and its text into the window. The board is built in board units, CELL_SIZE per
+// cell, under one `Board` entity that is scaled to the largest size that leaves room for the
+// winner banner above it, the scores below it and the move history beside it. The layout is
+// recomputed when the window is resized or the settings change the grid size.
+
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowResized};
+
+use crate::game::{Board, GridConfig, ScoreText, StatusText, WinnerText, CELL_SIZE};
+use crate::history::PANEL_WIDTH;
+
+const TEXT_BAND: f32 = 0.12; // Share of the window height kept for the text above and below the board
+const MARGIN: f32 = 0.04; // Share of the shorter window side left empty around the board
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+    pub scale: f32,       // Board units to window pixels
+    pub banner_y: f32,    // Centre of the winner banner
+    pub score_y: f32,     // Centre of the score text
+    pub banner_font: f32, // Font size of the winner banner
+    pub score_font: f32,  // Font size of the score text
+}
+
+impl Layout {
+    pub fn new(width: f32, height: f32, grid_size: usize) -> Self {
+        let band = height * TEXT_BAND;
+        let margin = width.min(height) * MARGIN;
+
+        // The board is square, so the tighter of the two directions decides its size. It stays
+        // centred, so the history panel's width is kept free on both sides.
+        let board = (width - 2.0 * (margin + PANEL_WIDTH))
+            .min(height - 2.0 * (band + margin))
+            .max(1.0);
+        let text_y = board / 2.0 + band / 2.0;
+
+        Layout {
+            scale: board / (grid_size as f32 * CELL_SIZE),
+            banner_y: text_y,
+            score_y: -text_y,
+            // The score line is long, so narrow windows shrink it further
+            banner_font: (band * 0.6).min(width / 10.0),
+            score_font: (band * 0.35).min(width / 26.0),
+        }
+    }
+}
+
+pub fn update_layout(
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    grid_config: Res<GridConfig>,
+    mut layout: ResMut<Layout>,
+) {
+    // Read every event so old resizes are not seen again next frame
+    let resized = resized.iter().count() > 0;
+    if !resized && !grid_config.is_changed() {
+        return;
+    }
+
+    if let Ok(window) = windows.get_single() {
+        let new_layout = Layout::new(window.width(), window.height(), grid_config.size);
+        if *layout != new_layout {
+            *layout = new_layout;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn apply_layout(
+    layout: Res<Layout>,
+    mut boards: Query<&mut Transform, With<Board>>,
+    mut banners: Query<(&mut Transform, &mut Text), (With<WinnerText>, Without<Board>)>,
+    mut scores: Query<
+        (&mut Transform, &mut Text),
+        (With<ScoreText>, Without<Board>, Without<WinnerText>),
+    >,
+    mut statuses: Query<
+        (&mut Transform, &mut Text),
+        (
+            With<StatusText>,
+            Without<Board>,
+            Without<WinnerText>,
+            Without<ScoreText>,
+        ),
+    >,
+) {
+    // A board rebuilt by the settings screen starts unscaled, so it is checked every frame
+    for mut transform in boards.iter_mut() {
+        if transform.scale.x != layout.scale {
+            transform.scale = Vec3::splat(layout.scale);
+        }
+    }
+
+    if !layout.is_changed() {
+        return;
+    }
+    for (mut transform, mut text) in banners.iter_mut() {
+        transform.translation.y = layout.banner_y;
+        text.sections[0].style.font_size = layout.banner_font;
+    }
+    for (mut transform, mut text) in scores.iter_mut() {
+        transform.translation.y = layout.score_y;
+        text.sections[0].style.font_size = layout.score_font;
+    }
+    for (mut transform, mut text) in statuses.iter_mut() {
+        transform.translation.y = layout.banner_y;
+        text.sections[0].style.font_size = layout.score_font;
+    }
+}
@@ -0,0 +1,20 @@
+// A graphical Tic-Tac-Toe game built on Bevy. Add `TicTacToePlugin` to an app to get the board,
+// the menus and the AI; the binary in main.rs does just that with options from the command line.
+
+pub mod ai;
+pub mod animation;
+pub mod focus;
+pub mod game;
+pub mod gamepad;
+pub mod headless;
+pub mod history;
+pub mod layout;
+pub mod menu;
+pub mod nn;
+pub mod online;
+pub mod plugin;
+pub mod settings;
+pub mod svg;
+pub mod theme;
+
+pub use plugin::{GameSet, InputOptions, TicTacToeConfig, TicTacToePlugin};
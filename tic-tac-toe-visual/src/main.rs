@@ -1,36 +1,136 @@
 use bevy::prelude::*;
 use bevy::log::LogPlugin; // Import LogPlugin for logging
 use std::env; // Import for command-line argument parsing
+use std::process;
 
-mod game;
+use tic_tac_toe_visual::online::OnlineSettings;
+use tic_tac_toe_visual::theme::{Palette, Theme};
+use tic_tac_toe_visual::{ai, nn, TicTacToeConfig, TicTacToePlugin};
 
 fn main() {
-    // Parse the grid size from command-line arguments
     let args: Vec<String> = env::args().collect();
+
+    // `--no-animations` may appear anywhere; the remaining arguments are positional
+    let animations = !args.iter().any(|arg| arg == "--no-animations");
+    let mut args: Vec<String> = args
+        .into_iter()
+        .filter(|arg| arg != "--no-animations")
+        .collect();
+
+    // So may `--theme <palette or theme file>`
+    let mut theme = None;
+    if let Some(index) = args.iter().position(|arg| arg == "--theme") {
+        theme = args.get(index + 1).cloned();
+        args.drain(index..(index + 2).min(args.len()));
+    }
+
+    // And `--online <address>`, where online games are hosted and joined
+    let mut online = None;
+    if let Some(index) = args.iter().position(|arg| arg == "--online") {
+        online = args.get(index + 1).cloned();
+        args.drain(index..(index + 2).min(args.len()));
+    }
+
+    // `train <size> <weights file> [rounds]` trains a network and exits without opening a window
+    if args.len() > 1 && args[1] == "train" {
+        train_network(&args[2..]);
+        return;
+    }
+
+    // Parse the grid size from command-line arguments
     let grid_size = if args.len() > 1 {
         args[1].parse::<usize>().unwrap_or(3) // Default to 3x3 if parsing fails
     } else {
         3 // Default to 3x3 if no argument is provided
     };
 
-    App::new()
-        .add_plugins(DefaultPlugins.set(LogPlugin {
-            level: bevy::log::Level::INFO, // Set log level to INFO
-            filter: "wgpu=warn".to_string(), // Suppress wgpu warnings
-        }))
-        .insert_resource(game::Score {
-            wins_x: 0,
-            wins_o: 0,
-            draws: 0,
-        }) // Initialize score tracking
-        .insert_resource(game::GridConfig { size: grid_size }) // Use the grid size from the command-line argument
-        .add_systems(Startup, game::setup)
-        .add_systems(Update, game::handle_clicks)
-        .add_systems(Update, game::update_grid)
-        .add_systems(Update, game::handle_restart) // Add restart system
-        .add_systems(Update, game::handle_ai_turn) // Add AI turn system
-        .add_systems(Update, game::log_game_record) // Log game record system
-        .add_systems(Update, game::update_winner_text) // Update winner banner
-        .add_systems(Update, game::update_score_text)  // Update score text
-        .run();
+    // Logging starts with the default plugins, so add them before loading the network
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(LogPlugin {
+        level: bevy::log::Level::INFO, // Set log level to INFO
+        filter: "wgpu=warn".to_string(), // Suppress wgpu warnings
+    }));
+
+    // The settings screen can change the rules and the opponent later
+    let mut config = TicTacToeConfig {
+        animations,
+        ..default()
+    };
+    config.grid.size = grid_size;
+    config.grid.win_length = grid_size; // Lines span the whole board
+    if let Some(address) = online {
+        config.online = OnlineSettings { address };
+    }
+
+    if let Some(theme) = theme {
+        match Palette::from_name(&theme) {
+            Some(palette) => config.theme = Theme::new(palette),
+            None => match Theme::load(&theme) {
+                Ok(loaded) => config.theme = loaded,
+                Err(err) => warn!("{}; using the default colours", err),
+            },
+        }
+    }
+
+    // An optional second argument loads a trained network for the AI opponent
+    if let Some(path) = args.get(2) {
+        match nn::Network::load(path) {
+            Ok(network) if network.size() == grid_size => {
+                config.network = Some(network);
+                config.ai.o = ai::Controller::Ai(ai::Engine {
+                    opponent: ai::Opponent::Network,
+                    difficulty: ai::Difficulty::Hard,
+                });
+            }
+            Ok(network) => warn!(
+                "Weights in {} are for a {}x{} grid; using random AI moves",
+                path,
+                network.size(),
+                network.size()
+            ),
+            Err(err) => warn!("{}; using random AI moves", err),
+        }
+    }
+
+    app.add_plugins(TicTacToePlugin { config }).run();
+}
+
+// Trains a network and writes it to the weights file. 3x3 boards learn from solver-labelled
+// positions; larger boards, where exhaustive search is impractical, learn from self-play.
+fn train_network(args: &[String]) {
+    let (size, path, rounds) = match (
+        args.first().and_then(|size| size.parse::<usize>().ok()),
+        args.get(1),
+        args.get(2).map(|rounds| rounds.parse::<usize>().ok()),
+    ) {
+        (Some(size), Some(path), None) if size >= 3 => (size, path, 50),
+        (Some(size), Some(path), Some(Some(rounds))) if size >= 3 && args.len() == 3 => {
+            (size, path, rounds)
+        }
+        _ => {
+            eprintln!("Usage: tic-tac-toe-visual train <size> <weights file> [rounds]");
+            process::exit(2);
+        }
+    };
+
+    let mut rng = rand::thread_rng();
+    let mut network = nn::Network::new(size, &[size * size * 8], &mut rng);
+
+    for round in 1..=rounds {
+        let samples = if size == 3 {
+            nn::solver_samples(size, 500, &mut rng)
+        } else {
+            network.self_play(100, &mut rng)
+        };
+        let loss = network.train(&samples, 0.01);
+        println!("Round {}/{}: loss {:.4}", round, rounds, loss);
+    }
+
+    match network.save(path) {
+        Ok(()) => println!("Saved weights to {}", path),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
 }
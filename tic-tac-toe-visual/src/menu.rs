@@ -0,0 +1,310 @@
+// App states and the screens that switch between them: the main menu, the pause screen and the
+// game-over screen. Game systems only run while their state is active. The settings screen is
+// in settings.rs.
+
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::ai::{AiSettings, Controllers};
+use crate::game::{GameEvents, GameState, Score};
+use crate::online::Role;
+use crate::theme::Theme;
+
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum AppState {
+    #[default]
+    MainMenu,
+    Playing,
+    GameOver,
+    Paused,
+    Settings,
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    HotSeat,      // Two people take turns at the same computer
+    VsAi,         // The settings pick which sides the computer plays; by default it plays O
+    Demo,         // The computer plays both sides
+    Online(Role), // Against someone on another computer, who hosts or joins the game
+}
+
+// What a menu button does when pressed
+#[derive(Component, Clone, Copy)]
+pub enum MenuButton {
+    Play(GameMode),
+    Settings,
+    Resume,
+    Rematch,
+    MainMenu,
+}
+
+#[derive(Component)]
+pub struct MainMenuScreen; // Marker for the main menu UI
+
+#[derive(Component)]
+pub struct PauseScreen; // Marker for the pause screen UI
+
+#[derive(Component)]
+pub struct GameOverScreen; // Marker for the game-over buttons
+
+pub fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>, theme: Res<Theme>) {
+    let font = asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf");
+    commands
+        .spawn((screen(theme.overlay), MainMenuScreen))
+        .with_children(|parent| {
+            parent.spawn(label("Tic-Tac-Toe", 80.0, &font, &theme));
+            parent.spawn(label("Choose a game mode", 30.0, &font, &theme));
+            spawn_button(
+                parent,
+                &font,
+                &theme,
+                "Hot Seat",
+                MenuButton::Play(GameMode::HotSeat),
+            );
+            spawn_button(
+                parent,
+                &font,
+                &theme,
+                "Versus AI",
+                MenuButton::Play(GameMode::VsAi),
+            );
+            spawn_button(
+                parent,
+                &font,
+                &theme,
+                "AI vs AI Demo",
+                MenuButton::Play(GameMode::Demo),
+            );
+            spawn_button(
+                parent,
+                &font,
+                &theme,
+                "Host Online Game",
+                MenuButton::Play(GameMode::Online(Role::Host)),
+            );
+            spawn_button(
+                parent,
+                &font,
+                &theme,
+                "Join Online Game",
+                MenuButton::Play(GameMode::Online(Role::Join)),
+            );
+            spawn_button(parent, &font, &theme, "Settings", MenuButton::Settings);
+        });
+}
+
+pub fn spawn_pause_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_mode: Res<GameMode>,
+    theme: Res<Theme>,
+) {
+    let font = asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf");
+    if *game_mode == GameMode::Demo {
+        // A paused demo keeps the board in view, with the buttons along the bottom edge
+        let mut root = screen(Color::NONE);
+        root.style.justify_content = JustifyContent::FlexEnd;
+        commands.spawn((root, PauseScreen)).with_children(|parent| {
+            parent.spawn(label(
+                "Demo paused: P to resume, - and = to change the speed",
+                24.0,
+                &font,
+                &theme,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_button(row, &font, &theme, "Resume", MenuButton::Resume);
+                    spawn_button(row, &font, &theme, "Main Menu", MenuButton::MainMenu);
+                });
+        });
+        return;
+    }
+
+    commands
+        .spawn((screen(theme.overlay), PauseScreen))
+        .with_children(|parent| {
+            parent.spawn(label("Paused", 60.0, &font, &theme));
+            parent.spawn(label("Press P or Escape to resume", 30.0, &font, &theme));
+            spawn_button(parent, &font, &theme, "Resume", MenuButton::Resume);
+            spawn_button(parent, &font, &theme, "Main Menu", MenuButton::MainMenu);
+        });
+}
+
+pub fn spawn_game_over_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<Theme>,
+) {
+    // The board and winner banner stay visible; the buttons sit along the bottom edge
+    let font = asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf");
+    let mut root = screen(Color::NONE);
+    root.style.flex_direction = FlexDirection::Row;
+    root.style.align_items = AlignItems::FlexEnd;
+    commands
+        .spawn((root, GameOverScreen))
+        .with_children(|parent| {
+            spawn_button(parent, &font, &theme, "Rematch", MenuButton::Rematch);
+            spawn_button(parent, &font, &theme, "Main Menu", MenuButton::MainMenu);
+        });
+}
+
+pub fn despawn_screen<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Shades every button on every screen as the pointer moves over and presses it
+#[allow(clippy::type_complexity)]
+pub fn highlight_buttons(
+    mut query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+    theme: Res<Theme>,
+) {
+    for (interaction, mut color) in query.iter_mut() {
+        *color = match interaction {
+            Interaction::Pressed => theme.button_pressed,
+            Interaction::Hovered => theme.button_hovered,
+            Interaction::None => theme.button,
+        }
+        .into();
+    }
+}
+
+pub fn handle_menu_buttons(
+    mut commands: Commands,
+    query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut game_state: ResMut<GameState>,
+    mut events: GameEvents,
+    mut score: ResMut<Score>,
+    mut next_state: ResMut<NextState<AppState>>,
+    ai_settings: Res<AiSettings>,
+) {
+    for (interaction, button) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            MenuButton::Play(mode) => {
+                // A new mode starts a new session
+                commands.insert_resource(mode);
+                commands.insert_resource(Controllers::new(mode, &ai_settings));
+                *score = Score {
+                    wins_x: 0,
+                    wins_o: 0,
+                    draws: 0,
+                };
+                events.reset(&mut game_state);
+                next_state.set(AppState::Playing);
+            }
+            MenuButton::Settings => next_state.set(AppState::Settings),
+            MenuButton::Resume => next_state.set(AppState::Playing),
+            MenuButton::Rematch => {
+                events.reset(&mut game_state);
+                next_state.set(AppState::Playing);
+            }
+            MenuButton::MainMenu => next_state.set(AppState::MainMenu),
+        }
+    }
+}
+
+pub fn toggle_pause(
+    keys: Res<Input<KeyCode>>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keys.any_just_pressed([KeyCode::P, KeyCode::Escape]) {
+        match state.get() {
+            AppState::Playing => next_state.set(AppState::Paused),
+            AppState::Paused => next_state.set(AppState::Playing),
+            _ => {}
+        }
+    }
+}
+
+// Switches to the game-over screen once a move ends the game
+pub fn check_game_over(game_state: Res<GameState>, mut next_state: ResMut<NextState<AppState>>) {
+    if game_state.game_over {
+        next_state.set(AppState::GameOver);
+    }
+}
+
+// A full-window UI node that lays its children out in a centred column
+pub(crate) fn screen(color: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(Val::Px(20.0)),
+            ..default()
+        },
+        background_color: color.into(),
+        z_index: ZIndex::Global(10), // Above the board
+        ..default()
+    }
+}
+
+pub(crate) fn label(value: &str, font_size: f32, font: &Handle<Font>, theme: &Theme) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: font.clone(),
+            font_size,
+            color: theme.text,
+        },
+    )
+    .with_style(Style {
+        margin: UiRect::all(Val::Px(10.0)),
+        ..default()
+    })
+}
+
+// Spawns a button labelled `value`; `action` is the component that says what it does
+pub(crate) fn spawn_button<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    font: &Handle<Font>,
+    theme: &Theme,
+    value: &str,
+    action: impl Component,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut button = parent.spawn((
+        ButtonBundle {
+            style: button_style(300.0),
+            background_color: theme.button.into(),
+            ..default()
+        },
+        action,
+    ));
+    button.with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size: 28.0,
+                color: theme.text,
+            },
+        ));
+    });
+    button
+}
+
+pub(crate) fn button_style(width: f32) -> Style {
+    Style {
+        width: Val::Px(width),
+        height: Val::Px(60.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
@@ -0,0 +1,752 @@
+// This file defines a small feed-forward neural network that evaluates board positions.
+// The network maps a board encoding to move probabilities (policy) and a value in [-1, 1].
+// It runs on the CPU in pure Rust, can be trained from solver-labelled positions or
+// self-play, and is saved to and loaded from a plain-text weights file.
+
+use rand::Rng;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::game::{check_winner, is_draw, opponent};
+
+const FILE_HEADER: &str = "tic-tac-toe-nn 1";
+
+// A fully connected layer; weights are stored row-major as [output][input].
+#[derive(Clone)]
+struct Layer {
+    inputs: usize,
+    outputs: usize,
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+}
+
+impl Layer {
+    fn new(inputs: usize, outputs: usize, rng: &mut impl Rng) -> Self {
+        // Xavier-style initialisation keeps tanh activations out of saturation
+        let limit = (6.0 / (inputs + outputs) as f32).sqrt();
+        Layer {
+            inputs,
+            outputs,
+            weights: (0..inputs * outputs)
+                .map(|_| rng.gen_range(-limit..limit))
+                .collect(),
+            biases: vec![0.0; outputs],
+        }
+    }
+
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        (0..self.outputs)
+            .map(|o| {
+                let row = &self.weights[o * self.inputs..(o + 1) * self.inputs];
+                self.biases[o] + row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>()
+            })
+            .collect()
+    }
+}
+
+// Weight of the prior against the mean value when the search picks a child
+const EXPLORATION: f32 = 1.5;
+
+// A node of the search tree, reached by playing `cell` from its parent
+struct Node {
+    cell: usize,
+    prior: f32,
+    visits: u32,
+    value_sum: f32, // Sum of the values backed up through the node, for the side that moved here
+    children: Vec<usize>,
+}
+
+impl Node {
+    fn new(cell: usize, prior: f32) -> Self {
+        Node {
+            cell,
+            prior,
+            visits: 0,
+            value_sum: 0.0,
+            children: Vec::new(),
+        }
+    }
+
+    // Mean value plus the exploration bonus
+    fn puct(&self, parent_visits: f32) -> f32 {
+        let mean = if self.visits == 0 {
+            0.0
+        } else {
+            self.value_sum / self.visits as f32
+        };
+        mean + EXPLORATION * self.prior * parent_visits.sqrt() / (1.0 + self.visits as f32)
+    }
+}
+
+// A training example: a position, the side to move, and the targets for both heads.
+#[derive(Clone)]
+pub struct Sample {
+    pub grid: Vec<Vec<Option<char>>>,
+    pub player: char,
+    pub policy: Vec<f32>, // Target move probabilities, one per cell (row-major)
+    pub value: f32,       // Target outcome for `player`: 1.0 win, 0.0 draw, -1.0 loss
+}
+
+#[derive(Clone)]
+pub struct Network {
+    size: usize,
+    layers: Vec<Layer>,
+}
+
+impl Network {
+    // Creates a randomly initialised network for a `size` x `size` board with the given hidden layer widths.
+    pub fn new(size: usize, hidden: &[usize], rng: &mut impl Rng) -> Self {
+        let cells = size * size;
+        let mut widths = vec![2 * cells];
+        widths.extend_from_slice(hidden);
+        widths.push(cells + 1); // One policy logit per cell plus the value output
+
+        let layers = widths
+            .windows(2)
+            .map(|pair| Layer::new(pair[0], pair[1], rng))
+            .collect();
+        Network { size, layers }
+    }
+
+    // Returns the board size this network was built for
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Encodes the grid from the point of view of `player`: one plane for their marks, one for the opponent's
+    pub fn encode(grid: &[Vec<Option<char>>], player: char) -> Vec<f32> {
+        let cells: Vec<Option<char>> = grid.iter().flatten().copied().collect();
+        let own = cells
+            .iter()
+            .map(|&cell| if cell == Some(player) { 1.0 } else { 0.0 });
+        let other = cells.iter().map(|&cell| match cell {
+            Some(mark) if mark != player => 1.0,
+            _ => 0.0,
+        });
+        own.chain(other).collect()
+    }
+
+    // Runs the network and returns every layer's activations, input first
+    fn forward(&self, input: Vec<f32>) -> Vec<Vec<f32>> {
+        let mut activations = vec![input];
+        for (i, layer) in self.layers.iter().enumerate() {
+            let mut output = layer.forward(activations.last().unwrap());
+            if i + 1 < self.layers.len() {
+                output.iter_mut().for_each(|x| *x = x.tanh());
+            }
+            activations.push(output);
+        }
+        activations
+    }
+
+    // Evaluates a position for `player`. Returns move probabilities over the cells (row-major,
+    // zero for occupied cells) and the expected outcome in [-1, 1]. `search` uses these as the
+    // priors and leaf values of its tree search.
+    pub fn evaluate(&self, grid: &[Vec<Option<char>>], player: char) -> (Vec<f32>, f32) {
+        let activations = self.forward(Self::encode(grid, player));
+        let output = activations.last().unwrap();
+        let cells = self.size * self.size;
+        let legal: Vec<bool> = grid.iter().flatten().map(|cell| cell.is_none()).collect();
+        (
+            masked_softmax(&output[..cells], &legal),
+            output[cells].tanh(),
+        )
+    }
+
+    // Picks the legal move with the highest policy probability, or None if the board is full
+    fn best_move(&self, grid: &[Vec<Option<char>>], player: char) -> Option<(usize, usize)> {
+        let (policy, _) = self.evaluate(grid, player);
+        (0..policy.len())
+            .filter(|&i| grid[i / self.size][i % self.size].is_none())
+            .max_by(|&a, &b| policy[a].total_cmp(&policy[b]))
+            .map(|i| (i / self.size, i % self.size))
+    }
+
+    // Runs `simulations` rounds of Monte Carlo tree search and returns the most visited move, or
+    // None if the board is full. Children are picked by PUCT: their mean value plus a bonus that
+    // grows with the network's prior and shrinks as they are visited. Leaves are scored by the
+    // value head, so no random playouts are needed on large boards. With no simulations it plays
+    // the policy's favourite move. Setting `cancelled` stops the search before its next round.
+    pub fn search(
+        &self,
+        grid: &[Vec<Option<char>>],
+        player: char,
+        simulations: usize,
+        cancelled: &AtomicBool,
+    ) -> Option<(usize, usize)> {
+        if simulations == 0 {
+            return self.best_move(grid, player);
+        }
+
+        let mut nodes = vec![Node::new(0, 1.0)];
+        for _ in 0..simulations {
+            if cancelled.load(Ordering::Relaxed) {
+                break;
+            }
+            let mut grid = grid.to_vec();
+            let mut player = player;
+            let mut path = vec![0];
+
+            // Walk down the tree through expanded nodes
+            let mut current = 0;
+            while !nodes[current].children.is_empty() {
+                let parent_visits = nodes[current].visits as f32;
+                current = *nodes[current]
+                    .children
+                    .iter()
+                    .max_by(|&&a, &&b| {
+                        nodes[a]
+                            .puct(parent_visits)
+                            .total_cmp(&nodes[b].puct(parent_visits))
+                    })
+                    .unwrap();
+                let index = nodes[current].cell;
+                grid[index / self.size][index % self.size] = Some(player);
+                player = opponent(player);
+                path.push(current);
+            }
+
+            // Score the leaf for the side to move, expanding it if the game goes on
+            let value = if check_winner(&grid).is_some() {
+                -1.0 // The previous move won
+            } else if is_draw(&grid) {
+                0.0
+            } else {
+                let (policy, value) = self.evaluate(&grid, player);
+                for (cell, &prior) in policy.iter().enumerate() {
+                    if grid[cell / self.size][cell % self.size].is_none() {
+                        nodes.push(Node::new(cell, prior));
+                        let child = nodes.len() - 1;
+                        nodes[current].children.push(child);
+                    }
+                }
+                value
+            };
+
+            // Each node holds its value for the side that moved into it, which alternates
+            let mut value = -value;
+            for &node in path.iter().rev() {
+                nodes[node].visits += 1;
+                nodes[node].value_sum += value;
+                value = -value;
+            }
+        }
+
+        nodes[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| nodes[child].visits)
+            .map(|&child| (nodes[child].cell / self.size, nodes[child].cell % self.size))
+    }
+
+    // Runs one pass of stochastic gradient descent over the samples and returns the mean loss.
+    // The loss is the policy cross-entropy plus the squared error of the value head.
+    pub fn train(&mut self, samples: &[Sample], learning_rate: f32) -> f32 {
+        let cells = self.size * self.size;
+        let mut total_loss = 0.0;
+
+        for sample in samples {
+            let activations = self.forward(Self::encode(&sample.grid, sample.player));
+            let output = activations.last().unwrap();
+            let legal: Vec<bool> = sample
+                .grid
+                .iter()
+                .flatten()
+                .map(|cell| cell.is_none())
+                .collect();
+            let policy = masked_softmax(&output[..cells], &legal);
+            let value = output[cells].tanh();
+
+            // Gradient of the loss with respect to the raw outputs
+            let mut delta: Vec<f32> = (0..cells)
+                .map(|i| {
+                    if legal[i] {
+                        policy[i] - sample.policy[i]
+                    } else {
+                        0.0
+                    }
+                })
+                .collect();
+            delta.push(2.0 * (value - sample.value) * (1.0 - value * value));
+
+            total_loss += (value - sample.value).powi(2)
+                - (0..cells)
+                    .filter(|&i| sample.policy[i] > 0.0)
+                    .map(|i| sample.policy[i] * policy[i].max(1e-7).ln())
+                    .sum::<f32>();
+
+            // Backpropagate through the layers, updating weights as we go
+            for l in (0..self.layers.len()).rev() {
+                let input = &activations[l];
+                let layer = &mut self.layers[l];
+
+                let mut previous = vec![0.0; layer.inputs];
+                for (o, &d) in delta.iter().enumerate() {
+                    let row = &mut layer.weights[o * layer.inputs..(o + 1) * layer.inputs];
+                    for (i, w) in row.iter_mut().enumerate() {
+                        previous[i] += *w * d;
+                        *w -= learning_rate * d * input[i];
+                    }
+                    layer.biases[o] -= learning_rate * d;
+                }
+
+                // Hidden activations are tanh, whose derivative is 1 - a^2
+                delta = previous
+                    .iter()
+                    .zip(input)
+                    .map(|(g, a)| g * (1.0 - a * a))
+                    .collect();
+            }
+        }
+
+        total_loss / samples.len().max(1) as f32
+    }
+
+    // Plays `games` games of the network against itself and labels every position with the move
+    // played and the final result. Moves are sampled from the policy so the games stay varied.
+    pub fn self_play(&self, games: usize, rng: &mut impl Rng) -> Vec<Sample> {
+        let mut samples = Vec::new();
+
+        for _ in 0..games {
+            let mut grid = vec![vec![None; self.size]; self.size];
+            let mut player = 'X';
+            let mut history: Vec<Sample> = Vec::new();
+
+            let winner = loop {
+                if let Some(winner) = check_winner(&grid) {
+                    break Some(winner);
+                }
+                if is_draw(&grid) {
+                    break None;
+                }
+
+                let (policy, _) = self.evaluate(&grid, player);
+                let index = sample_index(&policy, rng);
+                let mut target = vec![0.0; policy.len()];
+                target[index] = 1.0;
+                history.push(Sample {
+                    grid: grid.clone(),
+                    player,
+                    policy: target,
+                    value: 0.0,
+                });
+
+                grid[index / self.size][index % self.size] = Some(player);
+                player = if player == 'X' { 'O' } else { 'X' };
+            };
+
+            for mut sample in history {
+                sample.value = match winner {
+                    Some(mark) if mark == sample.player => 1.0,
+                    Some(_) => -1.0,
+                    None => 0.0,
+                };
+                samples.push(sample);
+            }
+        }
+
+        samples
+    }
+
+    // Writes the network to a plain-text weights file
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let widths: Vec<String> = std::iter::once(self.layers[0].inputs)
+            .chain(self.layers.iter().map(|layer| layer.outputs))
+            .map(|width| width.to_string())
+            .collect();
+
+        let mut contents = format!(
+            "{}\nsize {}\nlayers {}\n",
+            FILE_HEADER,
+            self.size,
+            widths.join(" ")
+        );
+        for layer in &self.layers {
+            contents += &join_floats("w", &layer.weights);
+            contents += &join_floats("b", &layer.biases);
+        }
+
+        fs::write(path, contents).map_err(|err| format!("Failed to write {}: {}", path, err))
+    }
+
+    // Reads a network previously written by `save`
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+        let mut lines = contents.lines();
+
+        if lines.next() != Some(FILE_HEADER) {
+            return Err(format!("{} is not a tic-tac-toe weights file", path));
+        }
+        let size: usize = parse_header(lines.next(), "size")?
+            .parse()
+            .map_err(|_| "Invalid board size in weights file".to_string())?;
+        let widths: Vec<usize> = parse_header(lines.next(), "layers")?
+            .split_whitespace()
+            .map(|width| {
+                width
+                    .parse()
+                    .map_err(|_| "Invalid layer width in weights file".to_string())
+            })
+            .collect::<Result<_, _>>()?;
+
+        if widths.len() < 2
+            || widths[0] != 2 * size * size
+            || widths[widths.len() - 1] != size * size + 1
+        {
+            return Err("Layer widths do not match the board size".to_string());
+        }
+
+        let mut layers = Vec::new();
+        for pair in widths.windows(2) {
+            let weights = parse_floats(lines.next(), "w", pair[0] * pair[1])?;
+            let biases = parse_floats(lines.next(), "b", pair[1])?;
+            layers.push(Layer {
+                inputs: pair[0],
+                outputs: pair[1],
+                weights,
+                biases,
+            });
+        }
+
+        Ok(Network { size, layers })
+    }
+}
+
+// Labels a position with its game-theoretic value by exhaustive search. The policy target spreads
+// evenly over every optimal move. Returns None for finished positions. Only practical on 3x3 boards.
+pub fn solver_sample(grid: &[Vec<Option<char>>], player: char) -> Option<Sample> {
+    if check_winner(grid).is_some() || is_draw(grid) {
+        return None;
+    }
+
+    let size = grid.len();
+    let mut grid = grid.to_vec();
+    let mut scores = vec![None; size * size];
+    for (index, score) in scores.iter_mut().enumerate() {
+        let (row, col) = (index / size, index % size);
+        if grid[row][col].is_none() {
+            grid[row][col] = Some(player);
+            *score = Some(-negamax(&mut grid, opponent(player), -2, 2));
+            grid[row][col] = None;
+        }
+    }
+
+    let best = scores.iter().flatten().copied().max()?;
+    let optimal = scores.iter().filter(|&&score| score == Some(best)).count() as f32;
+    let policy = scores
+        .iter()
+        .map(|&score| {
+            if score == Some(best) {
+                1.0 / optimal
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    Some(Sample {
+        grid,
+        player,
+        policy,
+        value: best as f32,
+    })
+}
+
+// Generates `count` solver-labelled samples from random positions reachable by legal play
+pub fn solver_samples(size: usize, count: usize, rng: &mut impl Rng) -> Vec<Sample> {
+    let mut samples = Vec::with_capacity(count);
+
+    while samples.len() < count {
+        let mut grid = vec![vec![None; size]; size];
+        let mut player = 'X';
+        let plies = rng.gen_range(0..size * size);
+
+        for _ in 0..plies {
+            if check_winner(&grid).is_some() || is_draw(&grid) {
+                break;
+            }
+            let empty: Vec<usize> = (0..size * size)
+                .filter(|&i| grid[i / size][i % size].is_none())
+                .collect();
+            let index = empty[rng.gen_range(0..empty.len())];
+            grid[index / size][index % size] = Some(player);
+            player = opponent(player);
+        }
+
+        if let Some(sample) = solver_sample(&grid, player) {
+            samples.push(sample);
+        }
+    }
+
+    samples
+}
+
+// Scores the position for `player` to move: 1 win, 0 draw, -1 loss
+fn negamax(grid: &mut [Vec<Option<char>>], player: char, mut alpha: i32, beta: i32) -> i32 {
+    if let Some(winner) = check_winner(grid) {
+        return if winner == player { 1 } else { -1 };
+    }
+    if is_draw(grid) {
+        return 0;
+    }
+
+    let size = grid.len();
+    let mut best = -2;
+    for index in 0..size * size {
+        let (row, col) = (index / size, index % size);
+        if grid[row][col].is_some() {
+            continue;
+        }
+        grid[row][col] = Some(player);
+        let score = -negamax(grid, opponent(player), -beta, -alpha);
+        grid[row][col] = None;
+
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+// Softmax over the legal entries only; illegal entries get probability zero
+fn masked_softmax(logits: &[f32], legal: &[bool]) -> Vec<f32> {
+    let max = logits
+        .iter()
+        .zip(legal)
+        .filter(|(_, &ok)| ok)
+        .map(|(&x, _)| x)
+        .fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = logits
+        .iter()
+        .zip(legal)
+        .map(|(&x, &ok)| if ok { (x - max).exp() } else { 0.0 })
+        .collect();
+    let sum: f32 = exps.iter().sum();
+    if sum > 0.0 {
+        exps.iter().map(|x| x / sum).collect()
+    } else {
+        exps
+    }
+}
+
+// Draws an index with probability proportional to its weight
+fn sample_index(weights: &[f32], rng: &mut impl Rng) -> usize {
+    let mut remaining = rng.gen::<f32>() * weights.iter().sum::<f32>();
+    for (index, &weight) in weights.iter().enumerate() {
+        if weight > 0.0 && remaining < weight {
+            return index;
+        }
+        remaining -= weight;
+    }
+    // Rounding can leave a little weight over; fall back to the last legal index
+    weights
+        .iter()
+        .rposition(|&weight| weight > 0.0)
+        .unwrap_or(0)
+}
+
+fn join_floats(tag: &str, values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+    format!("{} {}\n", tag, values.join(" "))
+}
+
+fn parse_header<'a>(line: Option<&'a str>, key: &str) -> Result<&'a str, String> {
+    line.and_then(|line| line.strip_prefix(key))
+        .map(str::trim)
+        .ok_or_else(|| format!("Missing '{}' line in weights file", key))
+}
+
+fn parse_floats(line: Option<&str>, tag: &str, expected: usize) -> Result<Vec<f32>, String> {
+    let values: Vec<f32> = parse_header(line, tag)?
+        .split_whitespace()
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("Invalid number '{}' in weights file", value))
+        })
+        .collect::<Result<_, _>>()?;
+    if values.len() != expected {
+        return Err(format!(
+            "Expected {} values on '{}' line, found {}",
+            expected,
+            tag,
+            values.len()
+        ));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::env;
+
+    fn grid(rows: &[&str]) -> Vec<Vec<Option<char>>> {
+        rows.iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| if c == '.' { None } else { Some(c) })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn temp_path(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("tic-tac-toe-nn-{}-{}", std::process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn saved_networks_load_with_the_same_outputs() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let network = Network::new(3, &[12, 6], &mut rng);
+        let path = temp_path("round-trip");
+        network.save(&path).unwrap();
+        let loaded = Network::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let position = grid(&["X..", ".O.", "..."]);
+        let (policy, value) = network.evaluate(&position, 'X');
+        let (loaded_policy, loaded_value) = loaded.evaluate(&position, 'X');
+        assert_eq!(loaded.size(), 3);
+        for (a, b) in policy.iter().zip(&loaded_policy) {
+            assert!((a - b).abs() < 1e-5);
+        }
+        assert!((value - loaded_value).abs() < 1e-5);
+    }
+
+    #[test]
+    fn truncated_or_mismatched_weights_files_are_rejected() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let path = temp_path("truncated");
+        Network::new(3, &[8], &mut rng).save(&path).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+
+        // Drop the last line, the output biases
+        let truncated: Vec<&str> = contents.lines().collect();
+        fs::write(&path, truncated[..truncated.len() - 1].join("\n")).unwrap();
+        assert_eq!(
+            Network::load(&path).err().unwrap(),
+            "Missing 'b' line in weights file"
+        );
+
+        // Claim a 4x4 board for 3x3 weights
+        fs::write(&path, contents.replace("size 3", "size 4")).unwrap();
+        assert_eq!(
+            Network::load(&path).err().unwrap(),
+            "Layer widths do not match the board size"
+        );
+
+        // Leave one weight out
+        let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+        let weights = lines
+            .iter_mut()
+            .find(|line| line.starts_with("w "))
+            .unwrap();
+        weights.truncate(weights.rfind(' ').unwrap());
+        let short = lines.join("\n");
+        fs::write(&path, short).unwrap();
+        assert_eq!(
+            Network::load(&path).err().unwrap(),
+            "Expected 144 values on 'w' line, found 143"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn the_softmax_skips_occupied_cells_and_sums_to_one() {
+        let policy = masked_softmax(&[3.0, 1.0, -2.0, 0.5], &[false, true, true, false]);
+        assert_eq!(policy[0], 0.0);
+        assert_eq!(policy[3], 0.0);
+        assert!(policy[1] > policy[2]);
+        assert!((policy.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let network = Network::new(3, &[8], &mut rng);
+        let (policy, _) = network.evaluate(&grid(&["XO.", "...", "..X"]), 'O');
+        assert_eq!((policy[0], policy[1], policy[8]), (0.0, 0.0, 0.0));
+        assert!((policy.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn the_solver_labels_won_and_drawn_positions() {
+        // X to move wins at once in the top right corner
+        let sample = solver_sample(&grid(&["XX.", "OO.", "..."]), 'X').unwrap();
+        assert_eq!(sample.value, 1.0);
+        assert_eq!(sample.policy[2], 1.0);
+
+        // O must take the bottom left corner to hold the draw
+        let sample = solver_sample(&grid(&["XOX", "XOO", ".X."]), 'O').unwrap();
+        assert_eq!(sample.value, 0.0);
+        assert_eq!(sample.policy[6], 1.0);
+
+        // O threatens two lines, so X loses whatever they play
+        let sample = solver_sample(&grid(&["OO.", "OXX", ".X."]), 'X').unwrap();
+        assert_eq!(sample.value, -1.0);
+
+        // Finished games have nothing to label
+        assert!(solver_sample(&grid(&["XXX", "OO.", "..."]), 'O').is_none());
+        assert!(solver_sample(&grid(&["XOX", "XOO", "OXX"]), 'X').is_none());
+    }
+
+    #[test]
+    fn training_lowers_the_loss() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let samples = solver_samples(3, 50, &mut rng);
+        let mut network = Network::new(3, &[36], &mut rng);
+
+        let first = network.train(&samples, 0.01);
+        let mut last = first;
+        for _ in 0..20 {
+            last = network.train(&samples, 0.01);
+        }
+        assert!(last < first, "loss went from {} to {}", first, last);
+    }
+
+    #[test]
+    fn the_search_takes_a_winning_move() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let network = Network::new(3, &[8], &mut rng);
+        let go = AtomicBool::new(false);
+        assert_eq!(
+            network.search(&grid(&["XX.", "OO.", "..."]), 'X', 200, &go),
+            Some((0, 2))
+        );
+        assert_eq!(
+            network.search(&grid(&["XOX", "XOO", "OXX"]), 'O', 200, &go),
+            None
+        );
+    }
+
+    #[test]
+    fn without_simulations_the_policy_picks_an_empty_cell() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let network = Network::new(3, &[8], &mut rng);
+        let go = AtomicBool::new(false);
+        let choice = network.search(&grid(&["XOX", "OX.", "XO."]), 'O', 0, &go);
+        assert!(matches!(choice, Some((1, 2)) | Some((2, 2))));
+        assert_eq!(
+            network.search(&grid(&["XOX", "XOO", "OXX"]), 'O', 0, &go),
+            None
+        );
+    }
+
+    #[test]
+    fn a_cancelled_search_stops_at_once() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let network = Network::new(9, &[8], &mut rng);
+        let stop = AtomicBool::new(true);
+        // Far more rounds than a test could wait for; none of them runs
+        assert_eq!(
+            network.search(&vec![vec![None; 9]; 9], 'X', usize::MAX, &stop),
+            None
+        );
+    }
+}
@@ -0,0 +1,416 @@
+// Online games between two copies of the game over TCP. One player hosts: their game listens on
+// the online address and plays X. The other joins by connecting to that address and plays O.
+// Both games first check that they use the same rules, then send each other their moves and
+// restarts as lines of text. Background threads wait on the socket, so the window keeps drawing
+// while the other player thinks.
+
+use bevy::prelude::*;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::ai::{Controller, Controllers};
+use crate::game::{GameEvents, GameReset, GameState, GridConfig, MovePlayed};
+use crate::menu::{AppState, GameMode};
+
+const ACCEPT_POLL: Duration = Duration::from_millis(50); // How often a waiting host checks in
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Host, // Waits for the other player and plays X
+    Join, // Connects to the host and plays O
+}
+
+// Where online games are hosted and joined, such as "192.168.1.20:7878"
+#[derive(Resource, Clone, Debug)]
+pub struct OnlineSettings {
+    pub address: String,
+}
+
+impl Default for OnlineSettings {
+    fn default() -> Self {
+        OnlineSettings {
+            address: "127.0.0.1:7878".to_string(),
+        }
+    }
+}
+
+// One line of the conversation between the two games
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Rules(String), // Sent first by both sides; the games only play if these match
+    Move(usize, usize),
+    Restart,
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Option<Message> {
+        let mut words = line.split_whitespace();
+        match words.next()? {
+            "rules" => Some(Message::Rules(words.collect::<Vec<_>>().join(" "))),
+            "move" => {
+                let row = words.next()?.parse().ok()?;
+                let col = words.next()?.parse().ok()?;
+                Some(Message::Move(row, col))
+            }
+            "restart" => Some(Message::Restart),
+            _ => None,
+        }
+    }
+
+    pub fn to_line(&self) -> String {
+        match self {
+            Message::Rules(rules) => format!("rules {}", rules),
+            Message::Move(row, col) => format!("move {} {}", row, col),
+            Message::Restart => "restart".to_string(),
+        }
+    }
+}
+
+// The rules both games must agree on, as sent in the first message
+pub fn rules(config: &GridConfig) -> String {
+    format!(
+        "{} {} {:?} {}",
+        config.size, config.win_length, config.variant, config.first_player
+    )
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkState {
+    Waiting, // For the other player to join, or for the host to answer
+    Connected,
+    Closed(String), // Why the game cannot go on, for the status line
+}
+
+// What the background threads report
+enum Incoming {
+    Connected,
+    Received(Message),
+    Closed(String),
+}
+
+// The link to the other player of an online game. Dropping it hangs up.
+#[derive(Resource)]
+pub struct Connection {
+    pub role: Role,
+    pub address: String, // For a host, the address it actually listens on
+    pub state: LinkState,
+    rules: String,
+    outgoing: Sender<Message>,
+    incoming: Mutex<Receiver<Incoming>>,
+    stream: Arc<Mutex<Option<TcpStream>>>, // Shut down when hanging up, which wakes the reader
+    closed: Arc<AtomicBool>,               // Stops a host that is still waiting
+    remote_resets: usize, // Restarts asked for by the other player, which are not sent back
+}
+
+impl Connection {
+    // Starts hosting or joining in the background. Hosts listen straight away, so the address
+    // they listen on is known, and a busy port is reported, before anyone joins.
+    pub fn open(role: Role, address: &str, rules: String) -> Self {
+        let (outgoing, outbox) = mpsc::channel();
+        let (inbox, incoming) = mpsc::channel();
+        let mut connection = Connection {
+            role,
+            address: address.to_string(),
+            state: LinkState::Waiting,
+            rules: rules.clone(),
+            outgoing,
+            incoming: Mutex::new(incoming),
+            stream: Arc::new(Mutex::new(None)),
+            closed: Arc::new(AtomicBool::new(false)),
+            remote_resets: 0,
+        };
+
+        let listener = match role {
+            Role::Host => {
+                let listener = TcpListener::bind(address).and_then(|listener| {
+                    listener.set_nonblocking(true)?;
+                    Ok(listener)
+                });
+                match listener {
+                    Ok(listener) => {
+                        if let Ok(local) = listener.local_addr() {
+                            connection.address = local.to_string();
+                        }
+                        Some(listener)
+                    }
+                    Err(err) => {
+                        connection.state =
+                            LinkState::Closed(format!("Could not host on {}: {}", address, err));
+                        return connection;
+                    }
+                }
+            }
+            Role::Join => None,
+        };
+
+        let address = connection.address.clone();
+        let shared = connection.stream.clone();
+        let closed = connection.closed.clone();
+        thread::spawn(move || {
+            let stream = match listener {
+                Some(listener) => accept(&listener, &closed),
+                None => TcpStream::connect(&address)
+                    .map_err(|err| format!("Could not join {}: {}", address, err)),
+            };
+            match stream {
+                Ok(stream) => talk(stream, rules, outbox, inbox, shared, &closed),
+                Err(reason) => {
+                    let _ = inbox.send(Incoming::Closed(reason));
+                }
+            }
+        });
+        connection
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state == LinkState::Connected
+    }
+
+    // What the status line shows while there is no one to play against
+    pub fn status(&self) -> Option<String> {
+        match &self.state {
+            LinkState::Waiting => Some(match self.role {
+                Role::Host => format!(
+                    "Hosting on {}: waiting for the other player to join\u{2026}",
+                    self.address
+                ),
+                Role::Join => format!("Joining {}\u{2026}", self.address),
+            }),
+            LinkState::Connected => None,
+            LinkState::Closed(reason) => Some(reason.clone()),
+        }
+    }
+
+    // A lost link reports itself through the incoming messages, so send errors are ignored
+    fn send(&self, message: Message) {
+        let _ = self.outgoing.send(message);
+    }
+
+    fn hang_up(&mut self, reason: String) {
+        self.state = LinkState::Closed(reason);
+        self.closed.store(true, Ordering::Relaxed);
+        if let Some(stream) = self.stream.lock().unwrap().take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.hang_up("Left the game".to_string());
+    }
+}
+
+// Waits for the other player, giving up once the connection is dropped
+fn accept(listener: &TcpListener, closed: &AtomicBool) -> Result<TcpStream, String> {
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream
+                    .set_nonblocking(false)
+                    .map_err(|err| format!("Could not accept the other player: {}", err))?;
+                return Ok(stream);
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                if closed.load(Ordering::Relaxed) {
+                    return Err("Stopped hosting".to_string());
+                }
+                thread::sleep(ACCEPT_POLL);
+            }
+            Err(err) => return Err(format!("Could not accept the other player: {}", err)),
+        }
+    }
+}
+
+// Sends the rules, then every outgoing message from another thread, and reads the other
+// player's messages on this one until either side hangs up
+fn talk(
+    stream: TcpStream,
+    rules: String,
+    outbox: Receiver<Message>,
+    inbox: Sender<Incoming>,
+    shared: Arc<Mutex<Option<TcpStream>>>,
+    closed: &AtomicBool,
+) {
+    let lost = || Incoming::Closed("Could not talk to the other player".to_string());
+    let (Ok(mut writer), Ok(hang_up)) = (stream.try_clone(), stream.try_clone()) else {
+        let _ = inbox.send(lost());
+        return;
+    };
+    *shared.lock().unwrap() = Some(hang_up);
+    if closed.load(Ordering::Relaxed) {
+        let _ = stream.shutdown(Shutdown::Both); // Dropped while the other player was joining
+        return;
+    }
+
+    // The rules go out before anything here can hang up, so the other player sees them
+    if writeln!(writer, "{}", Message::Rules(rules).to_line()).is_err() {
+        let _ = inbox.send(lost());
+        return;
+    }
+    let _ = inbox.send(Incoming::Connected);
+
+    thread::spawn(move || {
+        for message in outbox.iter() {
+            if writeln!(writer, "{}", message.to_line()).is_err() {
+                break;
+            }
+        }
+    });
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        match Message::parse(&line) {
+            Some(message) => {
+                if inbox.send(Incoming::Received(message)).is_err() {
+                    return; // The connection was dropped
+                }
+            }
+            None => warn!("Ignored an online message: {}", line),
+        }
+    }
+    let _ = inbox.send(Incoming::Closed("The other player left".to_string()));
+}
+
+// Opens a connection when an online game is picked, and hangs up when another mode is
+pub fn open_connection(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    settings: Res<OnlineSettings>,
+    grid_config: Res<GridConfig>,
+) {
+    match *mode {
+        GameMode::Online(role) => commands.insert_resource(Connection::open(
+            role,
+            &settings.address,
+            rules(&grid_config),
+        )),
+        _ => commands.remove_resource::<Connection>(),
+    }
+}
+
+// Hangs up when the players go back to the main menu
+pub fn close_connection(mut commands: Commands) {
+    commands.remove_resource::<Connection>();
+}
+
+// Plays the other player's moves and restarts as they arrive
+pub fn receive_remote(
+    connection: Option<ResMut<Connection>>,
+    mut game_state: ResMut<GameState>,
+    mut events: GameEvents,
+    grid_config: Res<GridConfig>,
+    controllers: Res<Controllers>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(mut connection) = connection else {
+        return;
+    };
+    let incoming: Vec<Incoming> = connection.incoming.lock().unwrap().try_iter().collect();
+    for incoming in incoming {
+        if matches!(connection.state, LinkState::Closed(_)) {
+            break; // Nothing the other player sends counts once the game cannot go on
+        }
+        match incoming {
+            Incoming::Connected => connection.state = LinkState::Connected,
+            Incoming::Closed(reason) => connection.hang_up(reason),
+            Incoming::Received(Message::Rules(theirs)) => {
+                if theirs != connection.rules {
+                    let reason = format!(
+                        "The other player's rules ({}) differ from these ({})",
+                        theirs, connection.rules
+                    );
+                    connection.hang_up(reason);
+                }
+            }
+            Incoming::Received(Message::Move(row, col)) => {
+                let empty = game_state
+                    .grid
+                    .get(row)
+                    .and_then(|cells| cells.get(col))
+                    .is_some_and(|cell| cell.is_none());
+                let their_turn = controllers.get(game_state.current_player) == Controller::Remote;
+                if empty && their_turn && !game_state.game_over {
+                    events.play(&mut game_state, row, col, &grid_config);
+                } else {
+                    warn!("Ignored an online move to row {}, column {}", row, col);
+                }
+            }
+            Incoming::Received(Message::Restart) => {
+                connection.remote_resets += 1;
+                events.reset(&mut game_state);
+                next_state.set(AppState::Playing); // From the game-over screen too
+            }
+        }
+    }
+}
+
+// Sends the moves played at this computer and the restarts asked for here to the other player
+pub fn send_local(
+    connection: Option<ResMut<Connection>>,
+    mut moves: EventReader<MovePlayed>,
+    mut resets: EventReader<GameReset>,
+    game_state: Res<GameState>,
+    controllers: Res<Controllers>,
+) {
+    let Some(mut connection) = connection else {
+        moves.clear();
+        resets.clear();
+        return;
+    };
+    for &MovePlayed { row, col } in moves.iter() {
+        let player = game_state.grid[row][col];
+        if player.is_some_and(|player| controllers.get(player) == Controller::Human) {
+            connection.send(Message::Move(row, col));
+        }
+    }
+    for _ in resets.iter() {
+        if connection.remote_resets > 0 {
+            connection.remote_resets -= 1; // Already done by the other player
+        } else if connection.is_connected() {
+            connection.send(Message::Restart);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Variant;
+
+    #[test]
+    fn messages_survive_a_round_trip() {
+        for message in [
+            Message::Rules("3 3 Standard X".to_string()),
+            Message::Move(2, 0),
+            Message::Restart,
+        ] {
+            assert_eq!(Message::parse(&message.to_line()), Some(message));
+        }
+    }
+
+    #[test]
+    fn malformed_lines_are_not_messages() {
+        for line in ["", "move 1", "move a b", "resign"] {
+            assert_eq!(Message::parse(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn rules_name_everything_both_games_must_share() {
+        let config = GridConfig {
+            size: 4,
+            win_length: 3,
+            variant: Variant::Misere,
+            first_player: 'O',
+        };
+        assert_eq!(rules(&config), "4 3 Misere O");
+    }
+}
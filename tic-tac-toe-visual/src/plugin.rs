@@ -0,0 +1,269 @@
+// The whole game as one Bevy plugin. Add `TicTacToePlugin` to an app that has the default
+// plugins to get the board, the menus and the AI; `TicTacToeConfig` picks the starting rules,
+// the opponent, the colours and which inputs the game listens to.
+
+use bevy::prelude::*;
+
+use crate::ai::{self, AiSettings, Controller, Controllers, Difficulty, Engine, Opponent};
+use crate::animation::{self, Animations};
+use crate::focus::{self, Focus};
+use crate::game::{self, AiNetwork, GameState, GridConfig, Score, Variant};
+use crate::gamepad::{self, GamepadSeats};
+use crate::history::{self, Review};
+use crate::layout;
+use crate::menu::{self, AppState, GameMode};
+use crate::nn::Network;
+use crate::online::{self, OnlineSettings};
+use crate::settings::{self, Settings};
+use crate::svg;
+use crate::theme::{self, Theme};
+
+// The stages of a frame, run in this order
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameSet {
+    Input,   // Reads the mouse, keyboard, menus and AI, and changes the game
+    Results, // Scores, logs and announces finished games and switches screens
+    Visuals, // Brings the board, text and animations up to date
+}
+
+// Which inputs the game reacts to; menu buttons always work
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct InputOptions {
+    pub mouse: bool,    // Left-click a cell to play it; cell selections work either way
+    pub keyboard: bool, // Game keys such as R to restart, P to pause and E to export
+    pub gamepad: bool,  // D-pad or stick, A to play, Start to restart and Select for the pause menu
+}
+
+#[derive(Clone)]
+pub struct TicTacToeConfig {
+    pub grid: GridConfig,
+    pub ai: AiSettings,
+    pub network: Option<Network>, // Trained network for the neural network engine
+    pub theme: Theme,
+    pub online: OnlineSettings, // Where online games are hosted and joined
+    pub input: InputOptions,
+    pub animations: bool,
+    pub spawn_camera: bool, // Turn off when the host app has its own 2D camera
+}
+
+impl Default for TicTacToeConfig {
+    fn default() -> Self {
+        TicTacToeConfig {
+            grid: GridConfig {
+                size: 3,
+                win_length: 3,
+                variant: Variant::Standard,
+                first_player: 'X',
+            },
+            ai: AiSettings {
+                x: Controller::Human,
+                o: Controller::Ai(Engine {
+                    opponent: Opponent::Random,
+                    difficulty: Difficulty::Hard,
+                }),
+                min_think_seconds: 0.5,
+            },
+            network: None,
+            theme: Theme::default(),
+            online: OnlineSettings::default(),
+            input: InputOptions {
+                mouse: true,
+                keyboard: true,
+                gamepad: true,
+            },
+            animations: true,
+            spawn_camera: true,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TicTacToePlugin {
+    pub config: TicTacToeConfig,
+}
+
+fn mouse_enabled(input: Res<InputOptions>) -> bool {
+    input.mouse
+}
+
+fn keyboard_enabled(input: Res<InputOptions>) -> bool {
+    input.keyboard
+}
+
+fn gamepad_enabled(input: Res<InputOptions>) -> bool {
+    input.gamepad
+}
+
+impl Plugin for TicTacToePlugin {
+    fn build(&self, app: &mut App) {
+        let config = &self.config;
+        app.insert_resource(GameState::new(&config.grid))
+            .insert_resource(Score {
+                wins_x: 0,
+                wins_o: 0,
+                draws: 0,
+            }) // Initialize score tracking
+            .insert_resource(Settings::new(&config.grid, &config.ai, &config.theme))
+            .insert_resource(config.grid.clone())
+            .insert_resource(config.ai)
+            .insert_resource(config.theme.clone())
+            .insert_resource(ClearColor(config.theme.background))
+            .insert_resource(config.online.clone())
+            .insert_resource(config.input)
+            .insert_resource(Animations {
+                enabled: config.animations,
+            })
+            .init_resource::<Review>() // Start on the live game
+            .insert_resource(Focus::new(config.grid.size))
+            .init_resource::<GamepadSeats>()
+            .add_event::<game::CellSelected>()
+            .add_event::<game::MovePlayed>()
+            .add_event::<game::GameEnded>()
+            .add_event::<game::GameReset>()
+            .add_state::<AppState>() // Start in the main menu
+            .insert_resource(GameMode::VsAi) // Replaced by the mode picked in the main menu
+            .insert_resource(Controllers::new(GameMode::VsAi, &config.ai))
+            .configure_sets(
+                Update,
+                (GameSet::Input, GameSet::Results, GameSet::Visuals).chain(),
+            )
+            .add_systems(Startup, (game::setup, history::spawn_history_panel));
+        if config.spawn_camera {
+            app.add_systems(Startup, game::spawn_camera);
+        }
+        if let Some(network) = &config.network {
+            app.insert_resource(AiNetwork {
+                network: network.clone(),
+            });
+        }
+
+        // Screens
+        app.add_systems(OnEnter(AppState::MainMenu), menu::spawn_main_menu)
+            .add_systems(OnEnter(AppState::MainMenu), online::close_connection)
+            .add_systems(
+                OnExit(AppState::MainMenu),
+                menu::despawn_screen::<menu::MainMenuScreen>,
+            )
+            .add_systems(OnEnter(AppState::Paused), menu::spawn_pause_screen)
+            .add_systems(
+                OnExit(AppState::Paused),
+                menu::despawn_screen::<menu::PauseScreen>,
+            )
+            .add_systems(OnEnter(AppState::GameOver), menu::spawn_game_over_screen)
+            .add_systems(
+                OnExit(AppState::GameOver),
+                menu::despawn_screen::<menu::GameOverScreen>,
+            )
+            .add_systems(OnEnter(AppState::Settings), settings::spawn_settings_screen)
+            .add_systems(
+                OnExit(AppState::Settings),
+                (
+                    menu::despawn_screen::<settings::SettingsScreen>,
+                    settings::apply_settings,
+                ),
+            );
+
+        // Input: everything that changes the game
+        app.add_systems(
+            Update,
+            (
+                menu::highlight_buttons,
+                menu::handle_menu_buttons, // Buttons on every screen
+                history::handle_history_buttons,
+                settings::handle_setting_buttons.run_if(in_state(AppState::Settings)),
+                menu::toggle_pause
+                    .run_if(keyboard_enabled)
+                    .run_if(in_state(AppState::Playing).or_else(in_state(AppState::Paused))),
+                game::read_mouse
+                    .run_if(mouse_enabled)
+                    .run_if(in_state(AppState::Playing)),
+                (focus::move_focus, focus::place_with_keys)
+                    .run_if(keyboard_enabled)
+                    .run_if(in_state(AppState::Playing)),
+                gamepad::track_gamepads.run_if(gamepad_enabled),
+                gamepad::gamepad_cursor
+                    .after(gamepad::track_gamepads)
+                    .run_if(gamepad_enabled)
+                    .run_if(in_state(AppState::Playing)),
+                gamepad::gamepad_buttons
+                    .after(gamepad::track_gamepads)
+                    .run_if(gamepad_enabled),
+                game::handle_selection
+                    .after(game::read_mouse)
+                    .after(focus::place_with_keys)
+                    .after(gamepad::gamepad_cursor)
+                    .run_if(in_state(AppState::Playing)),
+                game::handle_restart
+                    .run_if(keyboard_enabled)
+                    .run_if(in_state(AppState::Playing).or_else(in_state(AppState::GameOver))),
+                // A reset throws away the move being searched for, before a new search starts
+                ai::cancel_ai_turn
+                    .after(game::handle_restart)
+                    .after(gamepad::gamepad_buttons)
+                    .after(menu::handle_menu_buttons),
+                (ai::finish_ai_turn, ai::start_ai_turn)
+                    .chain()
+                    .after(ai::cancel_ai_turn)
+                    .after(game::handle_selection)
+                    .run_if(in_state(AppState::Playing)),
+                // The other player of an online game
+                (
+                    online::open_connection
+                        .after(menu::handle_menu_buttons)
+                        .run_if(resource_changed::<GameMode>()),
+                    online::receive_remote
+                        .run_if(in_state(AppState::Playing).or_else(in_state(AppState::GameOver))),
+                ),
+                svg::export_svg.run_if(keyboard_enabled),
+                ai::change_ai_pace
+                    .run_if(keyboard_enabled)
+                    .run_if(in_state(AppState::Playing).or_else(in_state(AppState::Paused))),
+            )
+                .in_set(GameSet::Input),
+        );
+
+        // Results: count, log and announce finished games
+        app.add_systems(
+            Update,
+            (
+                game::record_result, // Count each result once
+                game::log_game_record.after(game::record_result),
+                game::update_winner_text,
+                game::update_score_text,
+                game::update_status_text,
+                online::send_local,
+                menu::check_game_over.run_if(in_state(AppState::Playing)),
+            )
+                .in_set(GameSet::Results),
+        );
+
+        // Visuals: keep the board inside the window, draw the marks and animate them
+        app.add_systems(
+            Update,
+            (
+                layout::update_layout,
+                layout::apply_layout.after(layout::update_layout),
+                game::update_grid,
+                focus::clamp_focus,
+                focus::show_focus.after(focus::clamp_focus),
+                history::update_history_list,
+                theme::restyle.run_if(resource_changed::<Theme>()),
+                history::show_review.after(theme::restyle),
+                animation::skip_animations
+                    .run_if(keyboard_enabled)
+                    .before(animation::grow_marks),
+                animation::start_placement.after(game::update_grid),
+                animation::grow_marks.after(animation::start_placement),
+                animation::start_winning_line,
+                animation::draw_winning_line
+                    .after(animation::start_winning_line)
+                    .after(animation::skip_animations),
+                animation::start_fade_out.before(game::update_grid),
+                animation::fade_out
+                    .after(animation::start_fade_out)
+                    .after(animation::skip_animations),
+            )
+                .in_set(GameSet::Visuals),
+        );
+    }
+}
@@ -0,0 +1,285 @@
+// The settings screen, reached from the main menu. It picks the board size, the length of a
+// winning line, the rule variant, who moves first, who plays each side and the colour palette.
+// Changes are kept in `Settings` while the screen is open and applied when it closes, rebuilding
+// the board.
+
+use bevy::prelude::*;
+
+use crate::ai::{AiSettings, Controller, Difficulty, Engine, Opponent};
+use crate::game::{self, Board, GameEvents, GameState, GridConfig, Variant};
+use crate::layout::Layout;
+use crate::menu::{self, MenuButton};
+use crate::theme::{Palette, Theme, PALETTES};
+
+const MIN_SIZE: usize = 3;
+const MAX_SIZE: usize = 9;
+const MIN_WIN_LENGTH: usize = 3;
+
+const VARIANTS: [Variant; 2] = [Variant::Standard, Variant::Misere];
+const CONTROLLERS: [Controller; 6] = [
+    Controller::Human,
+    computer(Opponent::Random, Difficulty::Hard),
+    computer(Opponent::Network, Difficulty::Hard), // Hard is for the fallback search
+    computer(Opponent::Minimax, Difficulty::Easy),
+    computer(Opponent::Minimax, Difficulty::Medium),
+    computer(Opponent::Minimax, Difficulty::Hard),
+];
+
+const fn computer(opponent: Opponent, difficulty: Difficulty) -> Controller {
+    Controller::Ai(Engine {
+        opponent,
+        difficulty,
+    })
+}
+
+// The choices being edited on the settings screen
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+pub struct Settings {
+    pub size: usize,
+    pub win_length: usize,
+    pub variant: Variant,
+    pub first_player: char,
+    pub x: Controller,
+    pub o: Controller,
+    pub palette: Palette,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Size,
+    WinLength,
+    Variant,
+    FirstPlayer,
+    PlayerX,
+    PlayerO,
+    Palette,
+}
+
+const FIELDS: [Field; 7] = [
+    Field::Size,
+    Field::WinLength,
+    Field::Variant,
+    Field::FirstPlayer,
+    Field::PlayerX,
+    Field::PlayerO,
+    Field::Palette,
+];
+
+// Steps a setting back or forward
+#[derive(Component, Clone, Copy)]
+pub struct SettingButton {
+    field: Field,
+    forward: bool,
+}
+
+// Text showing the current value of a setting
+#[derive(Component)]
+pub struct SettingValue(Field);
+
+#[derive(Component)]
+pub struct SettingsScreen; // Marker for the settings screen UI
+
+impl Settings {
+    pub fn new(config: &GridConfig, ai_settings: &AiSettings, theme: &Theme) -> Self {
+        Settings {
+            size: config.size,
+            win_length: config.win_length,
+            variant: config.variant,
+            first_player: config.first_player,
+            x: ai_settings.x,
+            o: ai_settings.o,
+            palette: theme.palette,
+        }
+    }
+
+    fn step(&mut self, field: Field, forward: bool) {
+        match field {
+            Field::Size => {
+                let full_lines = self.win_length == self.size;
+                self.size = step_number(self.size, MIN_SIZE, MAX_SIZE, forward);
+                // Full-length lines stay full-length; shorter lines must still fit
+                self.win_length = if full_lines {
+                    self.size
+                } else {
+                    self.win_length.min(self.size)
+                };
+            }
+            Field::WinLength => {
+                self.win_length = step_number(self.win_length, MIN_WIN_LENGTH, self.size, forward)
+            }
+            Field::Variant => self.variant = cycle(&VARIANTS, self.variant, forward),
+            Field::FirstPlayer => self.first_player = game::opponent(self.first_player),
+            Field::PlayerX => self.x = cycle(&CONTROLLERS, self.x, forward),
+            Field::PlayerO => self.o = cycle(&CONTROLLERS, self.o, forward),
+            Field::Palette => self.palette = cycle(&PALETTES, self.palette, forward),
+        }
+    }
+
+    fn describe(&self, field: Field) -> String {
+        match field {
+            Field::Size => format!("Board: {0}x{0}", self.size),
+            Field::WinLength => format!("Line to win: {}", self.win_length),
+            Field::Variant => match self.variant {
+                Variant::Standard => "Rules: Standard".to_string(),
+                Variant::Misere => "Rules: Misere (a line loses)".to_string(),
+            },
+            Field::FirstPlayer => format!("First move: Player {}", self.first_player),
+            Field::PlayerX => format!("Player X: {}", describe_controller(self.x)),
+            Field::PlayerO => format!("Player O: {}", describe_controller(self.o)),
+            Field::Palette => format!("Colours: {}", self.palette.name()),
+        }
+    }
+}
+
+fn describe_controller(controller: Controller) -> String {
+    match controller {
+        Controller::Human => "Human".to_string(),
+        Controller::Ai(engine) => match engine.opponent {
+            Opponent::Random => "AI, random".to_string(),
+            Opponent::Network => "AI, neural network".to_string(),
+            Opponent::Minimax => format!("AI, minimax ({:?})", engine.difficulty),
+        },
+        Controller::Remote => "Online".to_string(),
+    }
+}
+
+fn step_number(value: usize, min: usize, max: usize, forward: bool) -> usize {
+    if forward {
+        (value + 1).min(max)
+    } else {
+        value.saturating_sub(1).max(min)
+    }
+}
+
+// Moves to the next or previous choice, wrapping around at either end
+fn cycle<T: Copy + PartialEq>(choices: &[T], current: T, forward: bool) -> T {
+    let index = choices
+        .iter()
+        .position(|&choice| choice == current)
+        .unwrap_or(0);
+    let next = if forward {
+        (index + 1) % choices.len()
+    } else {
+        (index + choices.len() - 1) % choices.len()
+    };
+    choices[next]
+}
+
+pub fn spawn_settings_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    theme: Res<Theme>,
+) {
+    let font = asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf");
+    commands
+        .spawn((menu::screen(theme.overlay), SettingsScreen))
+        .with_children(|parent| {
+            parent.spawn(menu::label("Settings", 60.0, &font, &theme));
+            for field in FIELDS {
+                // "<" value ">" on one line
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        let back = SettingButton {
+                            field,
+                            forward: false,
+                        };
+                        menu::spawn_button(row, &font, &theme, "<", back)
+                            .insert(menu::button_style(60.0));
+                        row.spawn((
+                            menu::label(&settings.describe(field), 28.0, &font, &theme).with_style(
+                                Style {
+                                    width: Val::Px(400.0),
+                                    justify_content: JustifyContent::Center,
+                                    ..default()
+                                },
+                            ),
+                            SettingValue(field),
+                        ));
+                        let forward = SettingButton {
+                            field,
+                            forward: true,
+                        };
+                        menu::spawn_button(row, &font, &theme, ">", forward)
+                            .insert(menu::button_style(60.0));
+                    });
+            }
+            menu::spawn_button(parent, &font, &theme, "Back", MenuButton::MainMenu);
+        });
+}
+
+pub fn handle_setting_buttons(
+    query: Query<(&Interaction, &SettingButton), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+    mut values: Query<(&SettingValue, &mut Text)>,
+) {
+    for (interaction, button) in query.iter() {
+        if *interaction == Interaction::Pressed {
+            settings.step(button.field, button.forward);
+        }
+    }
+
+    // Changing one setting can change another, so every value is refreshed
+    if settings.is_changed() {
+        for (value, mut text) in values.iter_mut() {
+            text.sections[0].value = settings.describe(value.0);
+        }
+    }
+}
+
+// Applies the settings when the screen closes. New rules rebuild the board and start a new game;
+// new colours rebuild the board around the game in progress.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_settings(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    mut grid_config: ResMut<GridConfig>,
+    mut ai_settings: ResMut<AiSettings>,
+    mut game_state: ResMut<GameState>,
+    mut events: GameEvents,
+    layout: Res<Layout>,
+    mut theme: ResMut<Theme>,
+    boards: Query<Entity, With<Board>>,
+) {
+    // Who plays each side takes effect from the next game started from the main menu
+    ai_settings.x = settings.x;
+    ai_settings.o = settings.o;
+    // A palette picked here replaces any colours loaded from a theme file
+    let restyled = theme.palette != settings.palette;
+    if restyled {
+        *theme = Theme::new(settings.palette);
+    }
+
+    let config = GridConfig {
+        size: settings.size,
+        win_length: settings.win_length,
+        variant: settings.variant,
+        first_player: settings.first_player,
+    };
+    let rules_changed = Settings::new(&grid_config, &ai_settings, &theme) != *settings;
+    if !rules_changed && !restyled {
+        return; // Nothing about the board, its colours or the rules changed
+    }
+
+    // Replace the cells, their marks, the grid lines and any winning line; the new board is
+    // rescaled by apply_layout once the layout has caught up with the new size
+    for board in boards.iter() {
+        commands.entity(board).despawn_recursive();
+    }
+    let font = asset_server.load("fonts/Fira_Sans/FiraSans-Bold.ttf");
+    game::spawn_board(&mut commands, &font, config.size, layout.scale, &theme);
+
+    if rules_changed {
+        events.replace(&mut game_state, GameState::new(&config));
+        *grid_config = config;
+    }
+}
@@ -0,0 +1,157 @@
+// Draws boards as standalone SVG images, for documentation and bug reports. A single position is
+// drawn with its coordinates and the winning line; a whole game becomes a filmstrip with the board
+// after every move. The images are built as text by `tic_tac_toe_common::svg`, so exporting
+// needs no GPU. Press E in the game to export the board on screen and the game so far into the
+// working directory.
+
+use bevy::prelude::*;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tic_tac_toe_common::svg::{self, Frame, Style};
+
+use crate::game::{line_through, GameState, GridConfig, Line};
+use crate::history::{notation, Review};
+use crate::theme::Theme;
+
+// Renders one position
+pub fn board(grid: &[Vec<Option<char>>], line: Option<Line>, theme: &Theme) -> String {
+    svg::board(
+        &from_top(grid),
+        line.map(|line| flip(line, grid.len())),
+        &style(theme),
+    )
+}
+
+// Renders the board after every move of the game, left to right and top to bottom
+pub fn filmstrip(game_state: &GameState, win_length: usize, theme: &Theme) -> String {
+    let size = game_state.grid.len();
+    let mut frames: Vec<Frame> = game_state
+        .moves
+        .iter()
+        .enumerate()
+        .map(|(index, &(player, row, col))| {
+            let grid = game_state.board_after(index + 1);
+            let line = line_through(&grid, row, col, win_length);
+            Frame {
+                cells: from_top(&grid),
+                line: line.map(|line| flip(line, size)),
+                caption: format!("{}. {} {}", index + 1, player, notation(row, col)),
+            }
+        })
+        .collect();
+    if frames.is_empty() {
+        // An empty game still shows its board
+        frames.push(Frame {
+            cells: from_top(&game_state.board_after(0)),
+            line: None,
+            caption: "Start".to_string(),
+        });
+    }
+    svg::filmstrip(&frames, &style(theme))
+}
+
+// Writes the board on screen, which may be a reviewed position, and the whole game when E is
+// pressed. The files are named after the time in milliseconds so earlier exports are kept.
+pub fn export_svg(
+    keys: Res<Input<KeyCode>>,
+    game_state: Res<GameState>,
+    review: Res<Review>,
+    grid_config: Res<GridConfig>,
+    theme: Res<Theme>,
+) {
+    if !keys.just_pressed(KeyCode::E) {
+        return;
+    }
+
+    let shown = review.0.unwrap_or(game_state.moves.len());
+    let grid = game_state.board_after(shown);
+    let line = shown
+        .checked_sub(1)
+        .and_then(|last| game_state.moves.get(last))
+        .and_then(|&(_, row, col)| line_through(&grid, row, col, grid_config.win_length));
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis())
+        .unwrap_or_default();
+    let files = [
+        (
+            format!("tic-tac-toe-{}-board.svg", stamp),
+            board(&grid, line, &theme),
+        ),
+        (
+            format!("tic-tac-toe-{}-game.svg", stamp),
+            filmstrip(&game_state, grid_config.win_length, &theme),
+        ),
+    ];
+    for (path, image) in files {
+        match fs::write(&path, image) {
+            Ok(()) => info!("Exported {}", path),
+            Err(err) => warn!("Could not export to {}: {}", path, err),
+        }
+    }
+}
+
+// The images use the colours of the current theme
+fn style(theme: &Theme) -> Style {
+    Style {
+        marks: ['X', 'O'],
+        mark_colors: [hex(theme.mark('X')), hex(theme.mark('O'))],
+        background: hex(theme.background),
+        light_cell: hex(theme.light_cell),
+        dark_cell: hex(theme.dark_cell),
+        grid_line: hex(theme.grid_line),
+        winning_line: hex(theme.winning_line),
+        text: hex(theme.text),
+    }
+}
+
+// Row 0 is the bottom row on screen, but images are drawn from the top
+fn from_top(grid: &[Vec<Option<char>>]) -> Vec<Vec<Option<char>>> {
+    grid.iter().rev().cloned().collect()
+}
+
+fn flip(((start_row, start_col), (end_row, end_col)): Line, size: usize) -> Line {
+    (
+        (size - 1 - start_row, start_col),
+        (size - 1 - end_row, end_col),
+    )
+}
+
+// Formats a colour as "#rrggbb"; SVG fills here are opaque
+fn hex(color: Color) -> String {
+    let [r, g, b, _] = color.as_rgba_f32();
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_bottom_row_is_drawn_at_the_bottom() {
+        let mut grid = vec![vec![None; 3]; 3];
+        grid[0][0] = Some('X');
+        grid[2][2] = Some('O');
+        let image = board(&grid, Some(((0, 0), (0, 2))), &Theme::default());
+        // Cells are 60 pixels from (36, 12), so a1 is centred at (66, 162) and c3 at (186, 42)
+        assert!(image.contains("<text x=\"66\" y=\"162\""));
+        assert!(image.contains("<text x=\"186\" y=\"42\""));
+        assert!(image.contains("<line x1=\"66\" y1=\"162\" x2=\"186\" y2=\"162\""));
+    }
+
+    #[test]
+    fn marks_and_cells_use_the_theme() {
+        let theme = Theme::default();
+        let image = board(
+            &[vec![Some('X'), Some('O')], vec![None, None]],
+            None,
+            &theme,
+        );
+        assert!(image.contains(&format!("fill=\"{}\"", hex(theme.mark('X')))));
+        assert!(image.contains(&format!("fill=\"{}\"", hex(theme.mark('O')))));
+        assert!(image.contains(&format!("fill=\"{}\"/>", hex(theme.light_cell))));
+        assert!(image.contains(&format!("fill=\"{}\"/>", hex(theme.dark_cell))));
+    }
+}
@@ -0,0 +1,250 @@
+// Colours of the board and the menus. Systems read them from the `Theme` resource instead of
+// using literals, so an app embedding the board can restyle it. A theme starts from one of the
+// named palettes and can be loaded from a theme file that overrides some of its colours.
+
+use bevy::prelude::*;
+use std::fs;
+
+use crate::game::Mark;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Palette {
+    #[default]
+    Default,
+    Dark,
+    HighContrast,
+    Deuteranopia, // Orange and blue marks, told apart without red-green vision
+}
+
+pub const PALETTES: [Palette; 4] = [
+    Palette::Default,
+    Palette::Dark,
+    Palette::HighContrast,
+    Palette::Deuteranopia,
+];
+
+impl Palette {
+    // The name used by `--theme`, theme files and the settings screen
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Default => "default",
+            Palette::Dark => "dark",
+            Palette::HighContrast => "high-contrast",
+            Palette::Deuteranopia => "deuteranopia",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        PALETTES.into_iter().find(|palette| palette.name() == name)
+    }
+}
+
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub palette: Palette,  // The palette the colours started from
+    pub background: Color, // Behind the board, and behind exported images
+    pub light_cell: Color, // Cells alternate between the light and dark colours
+    pub dark_cell: Color,
+    pub grid_line: Color,
+    pub x_mark: Color,
+    pub o_mark: Color,
+    pub winning_line: Color,
+    pub focus: Color, // Outline of the cell picked with the keyboard or a gamepad
+    pub text: Color,  // Banner, scores, menus and the move history
+    pub button: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub button_selected: Color, // The move being reviewed in the history panel
+    pub overlay: Color,         // Behind the menus, over the board
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::new(Palette::Default)
+    }
+}
+
+impl Theme {
+    pub fn new(palette: Palette) -> Self {
+        let default = Theme {
+            palette,
+            background: Color::rgb(0.4, 0.4, 0.4),   // Mid grey
+            light_cell: Color::rgb(0.9, 0.9, 0.9),   // Light grey
+            dark_cell: Color::rgb(0.6, 0.6, 0.6),    // Dark grey
+            grid_line: Color::rgb(0.2, 0.2, 0.2),    // Charcoal
+            x_mark: Color::rgb(1.0, 0.0, 0.0),       // Red
+            o_mark: Color::rgb(0.0, 0.0, 1.0),       // Blue
+            winning_line: Color::rgb(0.1, 0.1, 0.1), // Near black
+            focus: Color::rgb(1.0, 0.8, 0.0),        // Amber
+            text: Color::WHITE,
+            button: Color::rgb(0.25, 0.25, 0.25),
+            button_hovered: Color::rgb(0.35, 0.35, 0.35),
+            button_pressed: Color::rgb(0.45, 0.45, 0.45),
+            button_selected: Color::rgb(0.45, 0.45, 0.2),
+            overlay: Color::rgba(0.0, 0.0, 0.0, 0.85),
+        };
+
+        match palette {
+            Palette::Default => default,
+            Palette::Dark => Theme {
+                background: Color::rgb(0.08, 0.08, 0.1),
+                light_cell: Color::rgb(0.3, 0.3, 0.33),
+                dark_cell: Color::rgb(0.2, 0.2, 0.23),
+                grid_line: Color::rgb(0.05, 0.05, 0.05),
+                x_mark: Color::rgb(1.0, 0.45, 0.45), // Light red
+                o_mark: Color::rgb(0.45, 0.65, 1.0), // Light blue
+                winning_line: Color::rgb(0.95, 0.95, 0.95),
+                text: Color::rgb(0.9, 0.9, 0.9),
+                button: Color::rgb(0.18, 0.18, 0.2),
+                button_hovered: Color::rgb(0.26, 0.26, 0.3),
+                button_pressed: Color::rgb(0.34, 0.34, 0.4),
+                button_selected: Color::rgb(0.4, 0.35, 0.1),
+                overlay: Color::rgba(0.0, 0.0, 0.0, 0.9),
+                ..default
+            },
+            Palette::HighContrast => Theme {
+                background: Color::BLACK,
+                light_cell: Color::WHITE,
+                dark_cell: Color::rgb(0.85, 0.85, 0.85),
+                grid_line: Color::BLACK,
+                x_mark: Color::rgb(0.7, 0.0, 0.0), // Dark red
+                o_mark: Color::rgb(0.0, 0.0, 0.6), // Navy
+                winning_line: Color::BLACK,
+                focus: Color::rgb(1.0, 0.0, 1.0), // Magenta
+                button: Color::rgb(0.2, 0.2, 0.2),
+                button_hovered: Color::rgb(0.0, 0.3, 0.6),
+                button_pressed: Color::rgb(0.0, 0.45, 0.9),
+                button_selected: Color::rgb(0.6, 0.5, 0.0),
+                overlay: Color::rgba(0.0, 0.0, 0.0, 0.95),
+                ..default
+            },
+            // Okabe-Ito colours, which stay distinct for the common forms of colour blindness
+            Palette::Deuteranopia => Theme {
+                x_mark: Color::rgb(0.84, 0.37, 0.0), // Vermillion
+                o_mark: Color::rgb(0.0, 0.45, 0.7),  // Blue
+                focus: Color::rgb(0.8, 0.47, 0.65),  // Reddish purple
+                ..default
+            },
+        }
+    }
+
+    // Reads a theme file: a "palette <name>" line picks the starting palette, and lines such as
+    // "x_mark #ff8800" override single colours. Blank lines and lines starting with # are skipped.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+        Theme::parse(&contents).map_err(|err| format!("{} in {}", err, path))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut theme = Theme::default();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(char::is_whitespace)
+                .map(|(key, value)| (key, value.trim()))
+                .ok_or_else(|| format!("Missing value on line {}", number + 1))?;
+
+            if key == "palette" {
+                let palette = Palette::from_name(value)
+                    .ok_or_else(|| format!("Unknown palette '{}' on line {}", value, number + 1))?;
+                theme = Theme::new(palette);
+                continue;
+            }
+            let color = Color::hex(value.trim_start_matches('#'))
+                .map_err(|_| format!("Invalid colour '{}' on line {}", value, number + 1))?;
+            *theme
+                .color_mut(key)
+                .ok_or_else(|| format!("Unknown colour '{}' on line {}", key, number + 1))? = color;
+        }
+        Ok(theme)
+    }
+
+    pub fn mark(&self, player: char) -> Color {
+        if player == 'X' {
+            self.x_mark
+        } else {
+            self.o_mark
+        }
+    }
+
+    fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+        let color = match key {
+            "background" => &mut self.background,
+            "light_cell" => &mut self.light_cell,
+            "dark_cell" => &mut self.dark_cell,
+            "grid_line" => &mut self.grid_line,
+            "x_mark" => &mut self.x_mark,
+            "o_mark" => &mut self.o_mark,
+            "winning_line" => &mut self.winning_line,
+            "focus" => &mut self.focus,
+            "text" => &mut self.text,
+            "button" => &mut self.button,
+            "button_hovered" => &mut self.button_hovered,
+            "button_pressed" => &mut self.button_pressed,
+            "button_selected" => &mut self.button_selected,
+            "overlay" => &mut self.overlay,
+            _ => return None,
+        };
+        Some(color)
+    }
+}
+
+// Recolours what outlives a theme change: the window background, the text beside the board and
+// the buttons of the move history. Boards and menus are rebuilt with the new colours.
+pub fn restyle(
+    theme: Res<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut texts: Query<&mut Text, Without<Mark>>,
+    mut buttons: Query<&mut BackgroundColor, With<Button>>,
+) {
+    clear_color.0 = theme.background;
+    for mut text in texts.iter_mut() {
+        for section in text.sections.iter_mut() {
+            section.style.color = theme.text;
+        }
+    }
+    for mut background in buttons.iter_mut() {
+        *background = theme.button.into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_files_start_from_a_palette_and_override_colours() {
+        let theme = Theme::parse("# Night play\npalette dark\n\nx_mark #ff8800\n").unwrap();
+        assert_eq!(theme.palette, Palette::Dark);
+        assert_eq!(theme.x_mark, Color::rgb_u8(0xff, 0x88, 0x00));
+        assert_eq!(theme.o_mark, Theme::new(Palette::Dark).o_mark);
+    }
+
+    #[test]
+    fn theme_files_report_the_bad_line() {
+        assert_eq!(
+            Theme::parse("palette sepia").unwrap_err(),
+            "Unknown palette 'sepia' on line 1"
+        );
+        assert_eq!(
+            Theme::parse("\nx_mark red").unwrap_err(),
+            "Invalid colour 'red' on line 2"
+        );
+        assert_eq!(
+            Theme::parse("border #000000").unwrap_err(),
+            "Unknown colour 'border' on line 1"
+        );
+    }
+
+    #[test]
+    fn palettes_are_found_by_name() {
+        for palette in PALETTES {
+            assert_eq!(Palette::from_name(palette.name()), Some(palette));
+        }
+        assert_eq!(Palette::from_name("sepia"), None);
+    }
+}
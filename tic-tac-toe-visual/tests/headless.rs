@@ -0,0 +1,581 @@
+// Plays games through the Bevy systems in a headless app and checks the game state, the scores
+// and the winner banner.
+
+use bevy::prelude::*;
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+use tic_tac_toe_visual::ai::{AiSettings, Controller, Difficulty, Engine, Opponent};
+use tic_tac_toe_visual::animation::WinLine;
+use tic_tac_toe_visual::game::{Board, Cell, GridConfig, ScoreText};
+use tic_tac_toe_visual::headless::Harness;
+use tic_tac_toe_visual::menu::{AppState, GameMode, MenuButton};
+use tic_tac_toe_visual::online::{Connection, LinkState, OnlineSettings, Role};
+use tic_tac_toe_visual::svg;
+use tic_tac_toe_visual::theme::{Palette, Theme};
+use tic_tac_toe_visual::TicTacToeConfig;
+
+fn hot_seat() -> Harness {
+    let mut harness = Harness::new(TicTacToeConfig::default());
+    harness.start(GameMode::HotSeat);
+    harness
+}
+
+fn play(harness: &mut Harness, moves: &[(usize, usize)]) {
+    for &(row, col) in moves {
+        harness.select(row, col);
+    }
+}
+
+#[test]
+fn starts_on_the_main_menu() {
+    let harness = Harness::new(TicTacToeConfig::default());
+    assert_eq!(harness.app_state(), AppState::MainMenu);
+}
+
+#[test]
+fn x_wins_the_bottom_row() {
+    let mut harness = hot_seat();
+    play(&mut harness, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]);
+    harness.step(5);
+
+    assert_eq!(harness.game_state().winner, Some('X'));
+    assert_eq!(harness.score().wins_x, 1);
+    assert_eq!(harness.winner_text(), "Player X wins!");
+    assert_eq!(harness.app_state(), AppState::GameOver);
+}
+
+#[test]
+fn occupied_cells_and_cells_off_the_board_are_ignored() {
+    let mut harness = hot_seat();
+    play(&mut harness, &[(1, 1), (1, 1), (3, 0)]);
+
+    let game_state = harness.game_state();
+    assert_eq!(game_state.grid[1][1], Some('X'));
+    assert_eq!(game_state.current_player, 'O');
+}
+
+#[test]
+fn restart_clears_the_board_and_keeps_the_score() {
+    let mut harness = hot_seat();
+    play(
+        &mut harness,
+        &[
+            (0, 0),
+            (1, 1),
+            (2, 2),
+            (0, 1),
+            (2, 1),
+            (2, 0),
+            (0, 2),
+            (1, 2),
+            (1, 0),
+        ],
+    );
+    assert_eq!(harness.winner_text(), "It's a draw!");
+
+    harness.press(KeyCode::R);
+    harness.step(1);
+
+    let game_state = harness.game_state();
+    assert!(!game_state.game_over);
+    assert!(game_state.grid.iter().flatten().all(|cell| cell.is_none()));
+    assert_eq!(harness.score().draws, 1);
+    assert_eq!(harness.winner_text(), "");
+    assert_eq!(harness.app_state(), AppState::Playing);
+}
+
+#[test]
+fn restart_removes_the_winning_line_from_the_board() {
+    let mut harness = hot_seat();
+    play(&mut harness, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]);
+    harness.step(1);
+    let world = &mut harness.app.world;
+    assert_eq!(world.query::<&WinLine>().iter(world).count(), 1);
+
+    harness.press(KeyCode::R);
+    harness.step(1);
+    let world = &mut harness.app.world;
+    assert_eq!(world.query::<&WinLine>().iter(world).count(), 0);
+    let children = world
+        .query_filtered::<&Children, With<Board>>()
+        .single(world)
+        .to_vec();
+    assert!(children
+        .iter()
+        .all(|&child| world.get_entity(child).is_some()));
+}
+
+const MINIMAX: Controller = Controller::Ai(Engine {
+    opponent: Opponent::Minimax,
+    difficulty: Difficulty::Hard,
+});
+
+fn with_ai(mode: GameMode, ai: AiSettings) -> Harness {
+    let mut harness = Harness::new(TicTacToeConfig {
+        ai,
+        ..TicTacToeConfig::default()
+    });
+    harness.start(mode);
+    harness
+}
+
+fn vs_ai(min_think_seconds: f32) -> Harness {
+    let ai = TicTacToeConfig::default().ai;
+    with_ai(
+        GameMode::VsAi,
+        AiSettings {
+            min_think_seconds,
+            ..ai
+        },
+    )
+}
+
+#[test]
+fn the_computer_answers_a_move() {
+    let mut harness = vs_ai(0.0);
+    harness.select(1, 1);
+    harness.wait_for_ai();
+
+    let marks = harness.game_state().grid.iter().flatten().flatten();
+    assert_eq!(marks.count(), 2);
+    assert_eq!(harness.game_state().current_player, 'X');
+    assert_eq!(harness.status_text(), "Move 3: Player X (human) to play");
+}
+
+#[test]
+fn restarting_cancels_the_computer_move() {
+    let mut harness = vs_ai(60.0);
+    harness.select(1, 1);
+    harness.step(1);
+    assert!(harness.ai_thinking());
+    assert_eq!(
+        harness.status_text(),
+        "Move 2: Player O (AI) is thinking\u{2026}"
+    );
+
+    // Clicks are ignored while the computer thinks
+    harness.select(0, 0);
+    assert_eq!(harness.game_state().grid[0][0], None);
+
+    harness.press(KeyCode::R);
+    harness.step(1);
+    assert!(!harness.ai_thinking());
+    assert!(harness
+        .game_state()
+        .grid
+        .iter()
+        .flatten()
+        .all(|cell| cell.is_none()));
+    assert_eq!(harness.status_text(), "Move 1: Player X (human) to play");
+}
+
+#[test]
+fn the_computer_can_play_x() {
+    let mut harness = with_ai(
+        GameMode::VsAi,
+        AiSettings {
+            x: MINIMAX,
+            o: Controller::Human,
+            min_think_seconds: 0.0,
+        },
+    );
+    harness.wait_for_ai();
+
+    let marks = harness.game_state().grid.iter().flatten().flatten();
+    assert_eq!(marks.collect::<Vec<_>>(), [&'X']);
+    assert_eq!(harness.game_state().current_player, 'O');
+}
+
+#[test]
+fn hot_seat_ignores_the_ai_settings() {
+    let mut harness = with_ai(
+        GameMode::HotSeat,
+        AiSettings {
+            x: MINIMAX,
+            o: MINIMAX,
+            min_think_seconds: 0.0,
+        },
+    );
+    harness.select(1, 1);
+    harness.step(5);
+
+    assert!(!harness.ai_thinking());
+    assert_eq!(harness.game_state().current_player, 'O');
+}
+
+#[test]
+fn the_demo_plays_a_whole_game() {
+    let mut harness = with_ai(
+        GameMode::Demo,
+        AiSettings {
+            x: Controller::Human, // Played by the demo engine
+            o: MINIMAX,
+            min_think_seconds: 0.0,
+        },
+    );
+    for _ in 0..9 {
+        harness.wait_for_ai();
+    }
+
+    assert!(harness.game_state().game_over);
+    let score = harness.score();
+    assert_eq!(score.wins_x + score.wins_o + score.draws, 1);
+    assert_eq!(harness.app_state(), AppState::GameOver);
+}
+
+#[test]
+fn the_speed_keys_change_the_move_delay() {
+    let mut harness = vs_ai(0.5);
+    harness.press(KeyCode::Equals);
+    assert_eq!(
+        harness.app.world.resource::<AiSettings>().min_think_seconds,
+        0.25
+    );
+
+    harness.press(KeyCode::Minus);
+    harness.press(KeyCode::Minus);
+    assert_eq!(
+        harness.app.world.resource::<AiSettings>().min_think_seconds,
+        1.0
+    );
+}
+
+#[test]
+fn reviewing_shows_an_old_position_without_changing_the_game() {
+    let mut harness = hot_seat();
+    play(&mut harness, &[(1, 1), (0, 0), (2, 2)]);
+    assert_eq!(harness.game_state().moves[1], ('O', 0, 0));
+
+    harness.review(Some(1));
+    let shown = harness.shown_board();
+    assert_eq!(shown[1][1], Some('X'));
+    assert_eq!(shown.iter().flatten().flatten().count(), 1);
+
+    // The board is read-only while reviewing
+    harness.select(0, 1);
+    assert_eq!(harness.game_state().moves.len(), 3);
+    assert_eq!(harness.game_state().current_player, 'O');
+
+    harness.review(None);
+    assert_eq!(harness.shown_board(), harness.game_state().grid);
+    // The click made while reviewing is not played once the live game is back
+    assert_eq!(harness.game_state().moves.len(), 3);
+}
+
+#[test]
+fn a_finished_game_exports_as_svg() {
+    let mut harness = hot_seat();
+    play(&mut harness, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]);
+    let game_state = harness.game_state();
+
+    let board = svg::board(&game_state.grid, Some(((0, 0), (0, 2))), &Theme::default());
+    assert!(board.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert_eq!(board.matches(">X</text>").count(), 3);
+    assert!(board.contains("stroke=\"#1a1a1a\" stroke-width=\"6\"")); // The winning line
+
+    // One captioned board per move, with the winning line on the last
+    let film = svg::filmstrip(game_state, 3, &Theme::default());
+    assert!(film.contains(">1. X a1</text>"));
+    assert!(film.contains(">5. X c1</text>"));
+    assert_eq!(film.matches("stroke-width=\"6\"").count(), 1);
+}
+
+#[test]
+fn the_keyboard_moves_the_focus_and_places_marks() {
+    let mut harness = hot_seat();
+    harness.press(KeyCode::Up); // Shows the focus on the centre cell
+    harness.press(KeyCode::Return);
+    harness.press(KeyCode::Up);
+    harness.press(KeyCode::A);
+    harness.press(KeyCode::Space);
+    harness.press(KeyCode::Right);
+    harness.press(KeyCode::Right);
+    harness.press(KeyCode::Right); // Stops at the edge
+    harness.press(KeyCode::Return);
+
+    let grid = &harness.game_state().grid;
+    assert_eq!(grid[1][1], Some('X'));
+    assert_eq!(grid[2][0], Some('O'));
+    assert_eq!(grid[2][2], Some('X'));
+}
+
+#[test]
+fn digits_place_marks_like_a_keypad() {
+    let mut harness = hot_seat();
+    harness.press(KeyCode::Key7);
+    harness.press(KeyCode::Numpad3);
+
+    let grid = &harness.game_state().grid;
+    assert_eq!(grid[2][0], Some('X')); // Top left
+    assert_eq!(grid[0][2], Some('O')); // Bottom right
+}
+
+#[test]
+fn two_gamepads_take_one_side_each() {
+    let mut harness = hot_seat();
+    harness.connect_gamepad(0);
+    harness.connect_gamepad(1);
+    harness.press_gamepad(0, GamepadButtonType::South); // The centre cell
+    harness.press_gamepad(0, GamepadButtonType::DPadDown); // Not X's turn any more
+    harness.press_gamepad(0, GamepadButtonType::South);
+    harness.press_gamepad(1, GamepadButtonType::DPadUp);
+    harness.press_gamepad(1, GamepadButtonType::South);
+
+    let grid = &harness.game_state().grid;
+    assert_eq!(grid[1][1], Some('X'));
+    assert_eq!(grid[2][1], Some('O'));
+    assert_eq!(harness.game_state().moves.len(), 2);
+}
+
+#[test]
+fn the_stick_moves_the_cursor_once_per_push() {
+    let mut harness = hot_seat();
+    harness.connect_gamepad(0);
+    harness.press_gamepad(0, GamepadButtonType::South); // The centre cell
+    harness.move_stick(0, -1.0, 0.0);
+    harness.move_stick(0, -0.9, 0.1); // Still held, so the cursor stays put
+    harness.move_stick(0, 0.0, 0.0);
+    harness.move_stick(0, 0.2, -0.8);
+    harness.press_gamepad(0, GamepadButtonType::South);
+
+    assert_eq!(harness.game_state().grid[0][0], Some('O'));
+}
+
+#[test]
+fn gamepad_start_restarts_and_select_pauses() {
+    let mut harness = hot_seat();
+    harness.connect_gamepad(0);
+    play(&mut harness, &[(0, 0), (1, 1)]);
+
+    harness.press_gamepad(0, GamepadButtonType::Select);
+    assert_eq!(harness.app_state(), AppState::Paused);
+    harness.press_gamepad(0, GamepadButtonType::Select);
+    assert_eq!(harness.app_state(), AppState::Playing);
+
+    harness.press_gamepad(0, GamepadButtonType::Start);
+    assert!(harness.game_state().moves.is_empty());
+    assert_eq!(harness.app_state(), AppState::Playing);
+}
+
+#[test]
+fn the_settings_switch_the_colour_palette() {
+    let mut harness = hot_seat();
+    play(&mut harness, &[(0, 0)]);
+    harness.change_settings(|settings| settings.palette = Palette::Dark);
+
+    let dark = Theme::new(Palette::Dark);
+    let world = &mut harness.app.world;
+    assert_eq!(*world.resource::<Theme>(), dark);
+    assert_eq!(world.resource::<ClearColor>().0, dark.background);
+    let mut cells = world.query_filtered::<&Sprite, With<Cell>>();
+    for sprite in cells.iter(world) {
+        assert!([dark.light_cell, dark.dark_cell].contains(&sprite.color));
+    }
+    let mut scores = world.query_filtered::<&Text, With<ScoreText>>();
+    assert_eq!(scores.single(world).sections[0].style.color, dark.text);
+
+    // The game in progress is kept
+    assert_eq!(harness.shown_board()[0][0], Some('X'));
+}
+
+#[test]
+fn the_status_line_follows_the_turns() {
+    let mut harness = hot_seat();
+    assert_eq!(harness.status_text(), "Move 1: Player X (human) to play");
+    harness.select(1, 1);
+    assert_eq!(harness.status_text(), "Move 2: Player O (human) to play");
+
+    // The winner banner takes over at the end
+    play(&mut harness, &[(0, 0), (0, 1), (2, 2), (2, 1)]);
+    assert_eq!(harness.winner_text(), "Player X wins!");
+    assert_eq!(harness.status_text(), "");
+}
+
+#[test]
+fn the_main_menu_offers_every_mode() {
+    let mut harness = Harness::new(TicTacToeConfig::default());
+    let world = &mut harness.app.world;
+    let modes: Vec<GameMode> = world
+        .query::<&MenuButton>()
+        .iter(world)
+        .filter_map(|button| match button {
+            MenuButton::Play(mode) => Some(*mode),
+            _ => None,
+        })
+        .collect();
+    for mode in [
+        GameMode::HotSeat,
+        GameMode::VsAi,
+        GameMode::Demo,
+        GameMode::Online(Role::Host),
+        GameMode::Online(Role::Join),
+    ] {
+        assert!(modes.contains(&mode), "no button for {:?}", mode);
+    }
+}
+
+// Hosts a game on a free port and joins it from a second game, each with its own rules
+fn online(host_grid: GridConfig, guest_grid: GridConfig) -> (Harness, Harness) {
+    let host = host(host_grid);
+    let guest = join(link(&host).address.clone(), guest_grid);
+    (host, guest)
+}
+
+fn host(grid: GridConfig) -> Harness {
+    let mut host = Harness::new(TicTacToeConfig {
+        grid,
+        online: OnlineSettings {
+            address: "127.0.0.1:0".to_string(),
+        },
+        ..default()
+    });
+    host.start(GameMode::Online(Role::Host));
+    host
+}
+
+fn join(address: String, grid: GridConfig) -> Harness {
+    let mut guest = Harness::new(TicTacToeConfig {
+        grid,
+        online: OnlineSettings { address },
+        ..default()
+    });
+    guest.start(GameMode::Online(Role::Join));
+    guest
+}
+
+fn link(harness: &Harness) -> &Connection {
+    harness.app.world.resource::<Connection>()
+}
+
+// Runs both games until `done` holds, giving the network threads time to deliver
+fn until(host: &mut Harness, guest: &mut Harness, done: impl Fn(&Harness, &Harness) -> bool) {
+    for _ in 0..400 {
+        if done(host, guest) {
+            return;
+        }
+        host.step(1);
+        guest.step(1);
+        thread::sleep(Duration::from_millis(5));
+    }
+    panic!("the online games did not catch up");
+}
+
+fn connected(host: &Harness, guest: &Harness) -> bool {
+    link(host).is_connected() && link(guest).is_connected()
+}
+
+#[test]
+fn online_games_share_moves_and_restarts() {
+    let grid = TicTacToeConfig::default().grid;
+    let (mut host, mut guest) = online(grid.clone(), grid);
+    until(&mut host, &mut guest, connected);
+
+    // The host plays X, and the guest cannot play it for them
+    guest.select(0, 0);
+    host.select(1, 1);
+    until(&mut host, &mut guest, |_, guest| {
+        guest.game_state().moves.len() == 1
+    });
+    assert_eq!(guest.game_state().grid[1][1], Some('X'));
+    assert_eq!(guest.game_state().grid[0][0], None);
+
+    // Then the guest plays O
+    host.select(0, 0);
+    guest.select(0, 2);
+    until(&mut host, &mut guest, |host, _| {
+        host.game_state().moves.len() == 2
+    });
+    assert_eq!(host.game_state().grid[0][2], Some('O'));
+    assert_eq!(host.game_state().grid[0][0], None);
+
+    // A restart on one side clears both boards, and is not sent back
+    host.press(KeyCode::R);
+    until(&mut host, &mut guest, |_, guest| {
+        guest.game_state().moves.is_empty()
+    });
+    host.select(2, 2);
+    until(&mut host, &mut guest, |_, guest| {
+        guest.game_state().moves.len() == 1
+    });
+    host.step(5);
+    assert_eq!(host.game_state().moves.len(), 1);
+}
+
+#[test]
+fn online_games_need_the_same_rules() {
+    let grid = TicTacToeConfig::default().grid;
+    let larger = GridConfig {
+        size: 4,
+        win_length: 4,
+        ..grid.clone()
+    };
+    let (mut host, mut guest) = online(larger, grid);
+    until(&mut host, &mut guest, |host, guest| {
+        !connected(host, guest) && link(host).state != LinkState::Waiting
+    });
+    assert_eq!(
+        link(&guest).state,
+        LinkState::Closed(
+            "The other player's rules (4 4 Standard X) differ from these (3 3 Standard X)"
+                .to_string()
+        )
+    );
+
+    // Neither side can move
+    host.select(0, 0);
+    assert!(host.game_state().moves.is_empty());
+}
+
+#[test]
+fn the_status_line_says_who_an_online_game_waits_for() {
+    let grid = TicTacToeConfig::default().grid;
+    let mut host = host(grid.clone());
+    let address = link(&host).address.clone();
+    host.step(1);
+    assert_eq!(
+        host.status_text(),
+        format!(
+            "Hosting on {}: waiting for the other player to join\u{2026}",
+            address
+        )
+    );
+
+    let mut guest = join(address, grid.clone());
+    until(&mut host, &mut guest, connected);
+    host.step(1);
+    assert_eq!(host.status_text(), "Move 1: Player X (human) to play");
+    assert_eq!(
+        guest.status_text(),
+        "Move 1: Player X (online): waiting for their move\u{2026}"
+    );
+
+    // Leaving for the main menu ends the game on the other side too
+    guest
+        .app
+        .world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::MainMenu);
+    until(&mut host, &mut guest, |host, _| {
+        link(host).state != LinkState::Connected
+    });
+    host.step(1);
+    assert_eq!(host.status_text(), "The other player left");
+
+    // Joining where no one hosts says why it failed
+    let free = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = free.local_addr().unwrap().to_string();
+    drop(free);
+    let mut guest = join(address.clone(), grid);
+    for _ in 0..400 {
+        if link(&guest).state != LinkState::Waiting {
+            break;
+        }
+        guest.step(1);
+        thread::sleep(Duration::from_millis(5));
+    }
+    guest.step(1);
+    assert!(guest
+        .status_text()
+        .starts_with(&format!("Could not join {}: ", address)));
+}
@@ -0,0 +1,107 @@
+// This file implements the computer players and the move search used for hints.
+// The minimax player runs the negamax search in `tic_tac_toe_common::search`, which the Bevy
+// game uses too. The search is exhaustive near the end of the game and depth-limited otherwise,
+// with the depth set by the difficulty. Positions where a depth-limited search stops are scored
+// by counting the lines each side can still complete, so Hard is only perfect on boards it can
+// search to the end.
+
+use crate::game::{Game, Variant};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use tic_tac_toe_common::search::{affordable_depth, Search};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AiKind {
+    Random,  // Picks any empty cell
+    Minimax, // Searches ahead for the best move
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ai {
+    pub kind: AiKind,
+    pub difficulty: Difficulty,
+}
+
+impl Ai {
+    // Chooses a move for the player to move, or None if the grid is full
+    pub fn choose_move(&self, game: &Game, rng: &mut impl Rng) -> Option<(usize, usize)> {
+        let empty = game.get_grid().empty_cells();
+        let blunder = match self.difficulty {
+            Difficulty::Easy => 0.4,
+            Difficulty::Medium | Difficulty::Hard => 0.0,
+        };
+
+        if self.kind == AiKind::Random || rng.gen_bool(blunder) {
+            return empty.choose(rng).copied();
+        }
+
+        let depth = match self.difficulty {
+            Difficulty::Easy => 1,
+            Difficulty::Medium => 2,
+            Difficulty::Hard => affordable_depth(empty.len()),
+        };
+        search(game, depth, rng)
+    }
+}
+
+// Returns the strongest move for the player to move, for use as a hint
+pub fn best_move(game: &Game, rng: &mut impl Rng) -> Option<(usize, usize)> {
+    let ai = Ai {
+        kind: AiKind::Minimax,
+        difficulty: Difficulty::Hard,
+    };
+    ai.choose_move(game, rng)
+}
+
+// Scores every move to the given depth and picks randomly among the best ones
+fn search(game: &Game, depth: usize, rng: &mut impl Rng) -> Option<(usize, usize)> {
+    let rules = game.rules();
+    let search = Search {
+        marks: [game.player(0).get_mark(), game.player(1).get_mark()],
+        win_length: rules.win_length,
+        misere: rules.variant == Variant::Misere,
+        cancelled: None,
+    };
+    search.best_moves(&game.get_grid().cells, game.current_side(), depth).choose(rng).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord;
+    use crate::game::Rules;
+    use crate::player::Player;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn position(size: usize, win_length: usize, cells: &[&str]) -> Game {
+        let rules = Rules { size, win_length, ..Rules::default() };
+        let x = Player::new("Player X".to_string(), 'X');
+        let o = Player::new("Player O".to_string(), 'O');
+        let mut game = Game::new(x, o, rules, 0);
+        for cell in cells {
+            let (row, col) = coord::parse_cell(&[cell], size).unwrap();
+            game.play_turn(row, col).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn open_lines_are_blocked_beyond_the_search_depth() {
+        // X threatens to make an open four, which wins two moves later than Hard can see on 9x9
+        let game = position(9, 5, &["c5", "a1", "d5", "a2", "e5"]);
+        let mut rng = StdRng::seed_from_u64(1);
+        let ai = Ai { kind: AiKind::Minimax, difficulty: Difficulty::Hard };
+        let (row, col) = ai.choose_move(&game, &mut rng).unwrap();
+        let cell = coord::to_algebraic(row, col, 9);
+        assert!(cell == "b5" || cell == "f5", "O played {}", cell);
+    }
+}
@@ -0,0 +1,190 @@
+// This file defines the commands understood by the game loop and parses them from a line of input.
+// Game commands act on the game in progress; meta commands manage the session, files and help.
+
+use crate::coord;
+
+pub enum GameCommand {
+    Move { row: usize, col: usize },
+    Undo,
+    Hint,
+    Resign,
+}
+
+pub enum MetaCommand {
+    New,
+    Save(String),
+    Load(String),
+    Export { path: String, whole_game: bool }, // An SVG image of the board, or of every move
+    Score,
+    Help,
+    Quit,
+}
+
+pub enum Command {
+    Game(GameCommand),
+    Meta(MetaCommand),
+}
+
+pub const HELP: &str = "\
+Game commands:
+  move <cell>        Place your mark; 'move' may be omitted. A cell can be written as
+                     'b2' (column letter, row number from the bottom), a keypad digit 1-9
+                     (7 is top left) or a row and column from the top left, e.g. '1 1'
+  undo               Take back the last move
+  hint               Suggest a move for the current player
+  resign             Give up the current game
+Other commands:
+  new                Start a new game; after a finished game, the other side moves first
+  save <file>        Save the current game to a file
+  load <file>        Load a game from a file
+  export svg <file>  Save the board as an SVG image; add 'game' for a filmstrip of every move
+  score              Show the scores for this session
+  help               Show this help
+  quit               Exit the game";
+
+// Parses one line of input into a command for a grid of the given size
+pub fn parse(input: &str, size: usize) -> Result<Command, String> {
+    let tokens: Vec<&str> = input.split_whitespace().collect();
+    let (name, args) = match tokens.split_first() {
+        Some((name, args)) => (name.to_lowercase(), args),
+        None => return Err("Please enter a command. Type 'help' for a list of commands.".to_string()),
+    };
+
+    match name.as_str() {
+        "move" => parse_move(args, size),
+        "undo" => no_args(&name, args, Command::Game(GameCommand::Undo)),
+        "hint" => no_args(&name, args, Command::Game(GameCommand::Hint)),
+        "resign" => no_args(&name, args, Command::Game(GameCommand::Resign)),
+        "new" => no_args(&name, args, Command::Meta(MetaCommand::New)),
+        "save" => one_file(&name, args).map(|path| Command::Meta(MetaCommand::Save(path))),
+        "load" => one_file(&name, args).map(|path| Command::Meta(MetaCommand::Load(path))),
+        "export" => parse_export(args),
+        "score" => no_args(&name, args, Command::Meta(MetaCommand::Score)),
+        "help" => no_args(&name, args, Command::Meta(MetaCommand::Help)),
+        "quit" | "exit" => no_args(&name, args, Command::Meta(MetaCommand::Quit)),
+        // A bare cell is shorthand for `move`
+        _ if coord::is_cell(&name) => parse_move(&tokens, size),
+        _ => Err(format!(
+            "Unknown command '{}'. Type 'help' for a list of commands.",
+            name
+        )),
+    }
+}
+
+// Parses the answer to the rematch question: Some(true) for yes, Some(false) for no
+pub fn parse_rematch(input: &str) -> Option<bool> {
+    match input.trim().to_lowercase().as_str() {
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_move(args: &[&str], size: usize) -> Result<Command, String> {
+    let (row, col) = coord::parse_cell(args, size)?;
+    Ok(Command::Game(GameCommand::Move { row, col }))
+}
+
+// Parses `export svg <file> [game]`
+fn parse_export(args: &[&str]) -> Result<Command, String> {
+    let (format, rest) = match args.split_first() {
+        Some((format, rest)) => (format.to_lowercase(), rest),
+        None => return Err("'export' needs a format and a file name, e.g. 'export svg board.svg'.".to_string()),
+    };
+    if format != "svg" {
+        return Err(format!("Cannot export to '{}'. The only format is 'svg'.", format));
+    }
+    match rest {
+        [path] => Ok(Command::Meta(MetaCommand::Export { path: path.to_string(), whole_game: false })),
+        [path, what] if what.eq_ignore_ascii_case("game") => {
+            Ok(Command::Meta(MetaCommand::Export { path: path.to_string(), whole_game: true }))
+        }
+        [] => Err("'export svg' needs a file name, e.g. 'export svg board.svg'.".to_string()),
+        _ => Err("'export svg' takes a file name and optionally 'game', e.g. 'export svg game.svg game'.".to_string()),
+    }
+}
+
+fn no_args(name: &str, args: &[&str], command: Command) -> Result<Command, String> {
+    if args.is_empty() {
+        Ok(command)
+    } else {
+        Err(format!("'{}' does not take any arguments.", name))
+    }
+}
+
+fn one_file(name: &str, args: &[&str]) -> Result<String, String> {
+    match args {
+        [path] => Ok(path.to_string()),
+        [] => Err(format!("'{}' needs a file name, e.g. '{} game.txt'.", name, name)),
+        _ => Err(format!("'{}' takes a single file name.", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, Rules};
+    use crate::player::Player;
+
+    fn error(input: &str, size: usize) -> String {
+        match parse(input, size) {
+            Ok(_) => panic!("'{}' should not parse", input),
+            Err(err) => err,
+        }
+    }
+
+    fn cell(input: &str, size: usize) -> (usize, usize) {
+        match parse(input, size) {
+            Ok(Command::Game(GameCommand::Move { row, col })) => (row, col),
+            Ok(_) => panic!("'{}' is not a move", input),
+            Err(err) => panic!("'{}' should parse: {}", input, err),
+        }
+    }
+
+    #[test]
+    fn unknown_commands_are_rejected() {
+        assert_eq!(error("jump", 3), "Unknown command 'jump'. Type 'help' for a list of commands.");
+        assert_eq!(error("   ", 3), "Please enter a command. Type 'help' for a list of commands.");
+        assert!(matches!(parse("QUIT", 3), Ok(Command::Meta(MetaCommand::Quit))));
+    }
+
+    #[test]
+    fn missing_and_extra_arguments_are_rejected() {
+        assert_eq!(error("save", 3), "'save' needs a file name, e.g. 'save game.txt'.");
+        assert_eq!(error("load a.txt b.txt", 3), "'load' takes a single file name.");
+        assert_eq!(error("undo 2", 3), "'undo' does not take any arguments.");
+        assert_eq!(error("export svg", 3), "'export svg' needs a file name, e.g. 'export svg board.svg'.");
+        assert_eq!(
+            error("move", 3),
+            "A move needs a cell such as 'b1', a keypad digit or a row and column, e.g. 'move 1 1' (got 0 values)."
+        );
+    }
+
+    #[test]
+    fn cells_off_the_board_are_rejected() {
+        assert_eq!(error("move d1", 3), "Unknown cell 'd1'. Columns are lettered a to c.");
+        assert_eq!(error("a4", 3), "Unknown cell 'a4'. Rows are numbered 1 to 3.");
+        assert_eq!(error("move 1 3", 3), "Column must be a number between 0 and 2, got '3'.");
+        assert_eq!(error("0", 3), "Keypad moves must be a digit from 1 to 9, got '0'.");
+    }
+
+    #[test]
+    fn moves_parse_with_or_without_the_command() {
+        assert_eq!(cell("move b2", 3), (1, 1));
+        assert_eq!(cell("a1", 3), (2, 0));
+        assert_eq!(cell("7", 3), (0, 0));
+        assert_eq!(cell("Move 0 2", 3), (0, 2));
+    }
+
+    #[test]
+    fn occupied_cells_parse_but_cannot_be_played() {
+        let x = Player::new("Player X".to_string(), 'X');
+        let o = Player::new("Player O".to_string(), 'O');
+        let mut game = Game::new(x, o, Rules::default(), 0);
+
+        let (row, col) = cell("b2", 3);
+        game.play_turn(row, col).unwrap();
+        let (row, col) = cell("1 1", 3);
+        assert_eq!(game.play_turn(row, col), Err("Cell is already occupied".to_string()));
+    }
+}
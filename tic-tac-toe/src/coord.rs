@@ -0,0 +1,149 @@
+// This file converts between grid coordinates and the notations players can type.
+// Three notations are accepted:
+// - Algebraic: a column letter followed by a row number, e.g. "b2". Row 1 is the bottom row.
+// - Numeric keypad: a single digit 1-9 laid out like a keypad (7 is top left), 3x3 grids only.
+// - Row and column: two numbers counted from 0 at the top left, e.g. "1 1".
+
+pub use tic_tac_toe_common::notation::file_label;
+use tic_tac_toe_common::notation;
+
+// Parses the tokens of a move into (row, col) on a grid of the given size
+pub fn parse_cell(tokens: &[&str], size: usize) -> Result<(usize, usize), String> {
+    match tokens {
+        [token] if token.chars().all(|c| c.is_ascii_digit()) => parse_numpad(token, size),
+        [token] => parse_algebraic(token, size),
+        [row, col] => Ok((parse_index("Row", row, size)?, parse_index("Column", col, size)?)),
+        _ => Err(format!(
+            "A move needs a cell such as '{}', a keypad digit or a row and column, e.g. 'move 1 1' (got {} values).",
+            to_algebraic(size - 1, 1, size),
+            tokens.len()
+        )),
+    }
+}
+
+// Checks whether a token looks like a cell rather than a command name
+pub fn is_cell(token: &str) -> bool {
+    let mut chars = token.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_digit() => true,
+        Some(first) if first.is_ascii_alphabetic() => {
+            let rest = chars.as_str();
+            !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit())
+        }
+        _ => false,
+    }
+}
+
+// Formats a cell in algebraic notation, e.g. (0, 0) on a 3x3 grid is "a3"
+pub fn to_algebraic(row: usize, col: usize, size: usize) -> String {
+    notation::cell_name(col, size - row)
+}
+
+fn parse_numpad(token: &str, size: usize) -> Result<(usize, usize), String> {
+    if size != 3 {
+        return Err(format!(
+            "Keypad digits only work on 3x3 grids. Use a cell such as '{}' or a row and column.",
+            to_algebraic(0, 0, size)
+        ));
+    }
+    match token.parse::<usize>() {
+        Ok(digit @ 1..=9) => Ok((2 - (digit - 1) / 3, (digit - 1) % 3)),
+        _ => Err(format!("Keypad moves must be a digit from 1 to 9, got '{}'.", token)),
+    }
+}
+
+fn parse_algebraic(token: &str, size: usize) -> Result<(usize, usize), String> {
+    let token = token.to_lowercase();
+    let mut chars = token.chars();
+    let letter = chars.next().unwrap_or(' ');
+    let last_file = file_label(size - 1);
+
+    if !('a'..=last_file).contains(&letter) {
+        return Err(format!(
+            "Unknown cell '{}'. Columns are lettered a to {}.",
+            token, last_file
+        ));
+    }
+    match chars.as_str().parse::<usize>() {
+        Ok(rank) if (1..=size).contains(&rank) => {
+            Ok((size - rank, (letter as u8 - b'a') as usize))
+        }
+        _ => Err(format!(
+            "Unknown cell '{}'. Rows are numbered 1 to {}.",
+            token, size
+        )),
+    }
+}
+
+fn parse_index(name: &str, token: &str, size: usize) -> Result<usize, String> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|&value| value < size)
+        .ok_or_else(|| {
+            format!(
+                "{} must be a number between 0 and {}, got '{}'.",
+                name,
+                size - 1,
+                token
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_digit_rows_parse_on_large_grids() {
+        assert_eq!(parse_cell(&["j10"], 10), Ok((0, 9)));
+        assert_eq!(parse_cell(&["a10"], 12), Ok((2, 0)));
+        assert_eq!(parse_cell(&["z26"], 26), Ok((0, 25)));
+        assert_eq!(to_algebraic(0, 9, 10), "j10");
+        assert!(is_cell("j10"));
+    }
+
+    #[test]
+    fn letters_may_be_upper_or_lower_case() {
+        assert_eq!(parse_cell(&["B2"], 3), parse_cell(&["b2"], 3));
+        assert_eq!(parse_cell(&["J10"], 10), Ok((0, 9)));
+    }
+
+    #[test]
+    fn cells_off_the_board_are_rejected() {
+        assert_eq!(
+            parse_cell(&["k1"], 10),
+            Err("Unknown cell 'k1'. Columns are lettered a to j.".to_string())
+        );
+        assert_eq!(
+            parse_cell(&["j11"], 10),
+            Err("Unknown cell 'j11'. Rows are numbered 1 to 10.".to_string())
+        );
+        assert_eq!(
+            parse_cell(&["c0"], 3),
+            Err("Unknown cell 'c0'. Rows are numbered 1 to 3.".to_string())
+        );
+        assert_eq!(
+            parse_cell(&["4", "0"], 4),
+            Err("Row must be a number between 0 and 3, got '4'.".to_string())
+        );
+        assert_eq!(
+            parse_cell(&["0", "-1"], 4),
+            Err("Column must be a number between 0 and 3, got '-1'.".to_string())
+        );
+    }
+
+    #[test]
+    fn keypad_digits_only_work_on_3x3_grids() {
+        assert_eq!(parse_cell(&["7"], 3), Ok((0, 0)));
+        assert_eq!(parse_cell(&["3"], 3), Ok((2, 2)));
+        assert_eq!(
+            parse_cell(&["5"], 4),
+            Err("Keypad digits only work on 3x3 grids. Use a cell such as 'a4' or a row and column.".to_string())
+        );
+        assert_eq!(
+            parse_cell(&["1"], 10),
+            Err("Keypad digits only work on 3x3 grids. Use a cell such as 'a10' or a row and column.".to_string())
+        );
+    }
+}
@@ -3,25 +3,87 @@
 use crate::grid::Grid;
 use crate::player::Player;
 
+use std::fs;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Variant {
+    Standard, // Completing a line wins
+    Misere,   // Completing a line loses
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Rules {
+    pub size: usize,       // Rows and columns in the grid
+    pub win_length: usize, // Marks in a row that end the game
+    pub variant: Variant,
+}
+
+impl Rules {
+    // Returns the side (0 for X, 1 for O) that wins when `side` completes a line
+    pub fn line_winner(&self, side: usize) -> usize {
+        match self.variant {
+            Variant::Standard => side,
+            Variant::Misere => 1 - side,
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            size: 3,
+            win_length: 3,
+            variant: Variant::Standard,
+        }
+    }
+}
+
 pub struct Game {
     grid: Grid,
-    current_player: Player,
+    players: [Player; 2],       // Player X is side 0 and Player O is side 1
+    rules: Rules,
+    first: usize,               // Side that made the first move
+    current: usize,             // Side to move
+    moves: Vec<(usize, usize)>, // Every move played so far, used for undo and saving
+    winner: Option<usize>,      // Side that won, by completing a line or by resignation
 }
 
 impl Game {
-    pub fn new(player_x: Player, _player_o: Player) -> Self {
+    pub fn new(player_x: Player, player_o: Player, rules: Rules, first: usize) -> Self {
         Game {
-            grid: Grid::new(),
-            current_player: player_x,
+            grid: Grid::new(rules.size),
+            players: [player_x, player_o],
+            rules,
+            first,
+            current: first,
+            moves: Vec::new(),
+            winner: None,
         }
     }
 
+    // Returns a new, empty game with the same players and rules
+    pub fn restart(&self) -> Game {
+        let [player_x, player_o] = self.players.clone();
+        Game::new(player_x, player_o, self.rules, self.first)
+    }
+
+    // Returns a new, empty game where the other side moves first
+    pub fn rematch(&self) -> Game {
+        let [player_x, player_o] = self.players.clone();
+        Game::new(player_x, player_o, self.rules, 1 - self.first)
+    }
+
     pub fn play_turn(&mut self, row: usize, col: usize) -> Result<(), String> {
+        if self.is_over() {
+            return Err("The game is over".to_string());
+        }
         if self.grid.is_occupied(row, col) {
             return Err("Cell is already occupied".to_string());
         }
-        self.grid.place_mark(row, col, self.current_player.get_mark());
-        if self.check_winner() {
+        self.grid.place_mark(row, col, self.get_current_player().get_mark());
+        self.moves.push((row, col));
+        if self.grid.has_line_through(row, col, self.rules.win_length) {
+            self.winner = Some(self.rules.line_winner(self.current));
             return Ok(());
         }
         if self.is_draw() {
@@ -31,41 +93,109 @@ impl Game {
         Ok(())
     }
 
-    pub fn check_winner(&self) -> bool {
-        let cells = &self.grid.cells;
+    // Takes back the last move and gives the turn back to the player who made it
+    pub fn undo(&mut self) -> Result<(), String> {
+        let (row, col) = self.moves.pop().ok_or("There are no moves to undo")?;
+        self.current = self.side_of(row, col);
+        self.grid.clear_mark(row, col);
+        self.winner = None;
+        Ok(())
+    }
 
-        // Check rows and columns
-        for i in 0..3 {
-            if cells[i][0] == cells[i][1] && cells[i][1] == cells[i][2] && cells[i][0].is_some() {
-                return true;
-            }
-            if cells[0][i] == cells[1][i] && cells[1][i] == cells[2][i] && cells[0][i].is_some() {
-                return true;
-            }
+    // Takes back the last move, and any computer moves before it, so the turn returns to a human
+    pub fn undo_turn(&mut self) -> Result<(), String> {
+        self.undo()?;
+        while self.get_current_player().ai.is_some() && !self.moves.is_empty() {
+            self.undo()?;
         }
+        Ok(())
+    }
 
-        // Check diagonals
-        if cells[0][0] == cells[1][1] && cells[1][1] == cells[2][2] && cells[0][0].is_some() {
-            return true;
-        }
-        if cells[0][2] == cells[1][1] && cells[1][1] == cells[2][0] && cells[0][2].is_some() {
-            return true;
-        }
+    // The current player gives up; the opponent wins
+    pub fn resign(&mut self) {
+        self.winner = Some(1 - self.current);
+    }
 
-        false
+    pub fn check_winner(&self) -> bool {
+        self.winner.is_some()
+    }
+
+    // Returns the side that won, if any
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
     }
 
     pub fn is_draw(&self) -> bool {
-        self.grid.cells.iter().all(|row| row.iter().all(|cell| cell.is_some()))
+        self.grid.is_full() && !self.check_winner()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.check_winner() || self.is_draw()
+    }
+
+    // Returns the ends of the line completed by the last move, if it completed one
+    pub fn winning_line(&self) -> Option<((usize, usize), (usize, usize))> {
+        let &(row, col) = self.moves.last()?;
+        self.grid.line_through(row, col, self.rules.win_length)
     }
 
     fn switch_player(&mut self) {
         // Switch between Player X and Player O
-        if self.current_player.get_mark() == 'X' {
-            self.current_player = Player::new("Player O".to_string(), 'O');
+        self.current = 1 - self.current;
+    }
+
+    // Returns the side whose mark is in the given cell
+    pub fn side_of(&self, row: usize, col: usize) -> usize {
+        if self.grid.cells[row][col] == Some(self.players[0].get_mark()) {
+            0
         } else {
-            self.current_player = Player::new("Player X".to_string(), 'X');
+            1
+        }
+    }
+
+    // Writes the game to a file: a "first x" or "first o" line naming the side that moved first,
+    // then the moves played so far, one "row col" pair per line
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut contents = format!("first {}\n", ["x", "o"][self.first]);
+        for (row, col) in &self.moves {
+            contents += &format!("{} {}\n", row, col);
+        }
+        fs::write(path, contents).map_err(|err| format!("Could not save to {}: {}", path, err))
+    }
+
+    // Replays the moves from a file written by `save` into a new game with the same players and rules.
+    // Files without a "first" line are replayed with this game's first side.
+    pub fn load(&self, path: &str) -> Result<Game, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| format!("Could not load {}: {}", path, err))?;
+        let mut lines = contents.lines().enumerate().peekable();
+        let mut first = self.first;
+        if let Some((_, line)) = lines.next_if(|(_, line)| line.starts_with("first")) {
+            first = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                ["first", "x"] => 0,
+                ["first", "o"] => 1,
+                _ => return Err(format!("{} line 1: the first side must be 'x' or 'o', got '{}'", path, line)),
+            };
+        }
+
+        let [player_x, player_o] = self.players.clone();
+        let mut game = Game::new(player_x, player_o, self.rules, first);
+        let size = self.rules.size;
+
+        for (number, line) in lines {
+            let coords: Vec<usize> = line
+                .split_whitespace()
+                .map(|x| x.parse().ok().filter(|&x| x < size))
+                .collect::<Option<_>>()
+                .ok_or_else(|| format!("{} line {}: invalid move '{}'", path, number + 1, line))?;
+            if coords.len() != 2 {
+                return Err(format!("{} line {}: invalid move '{}'", path, number + 1, line));
+            }
+            game.play_turn(coords[0], coords[1])
+                .map_err(|err| format!("{} line {}: {}", path, number + 1, err))?;
         }
+
+        Ok(game)
     }
 
     // Getter for the grid
@@ -73,10 +203,90 @@ impl Game {
         &self.grid
     }
 
+    // Getter for the rules
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    // Getter for the moves played so far, oldest first
+    pub fn moves(&self) -> &[(usize, usize)] {
+        &self.moves
+    }
+
+    // Getter for a player by side (0 for X, 1 for O)
+    pub fn player(&self, side: usize) -> &Player {
+        &self.players[side]
+    }
+
+    // Getter for the side that moved first
+    pub fn first_side(&self) -> usize {
+        self.first
+    }
+
+    // Getter for the side to move
+    pub fn current_side(&self) -> usize {
+        self.current
+    }
+
     // Getter for the current player
     pub fn get_current_player(&self) -> &Player {
-        &self.current_player
+        &self.players[self.current]
     }
 }
 
-// Additional structs and methods for Grid and Player would be defined in their respective files.
\ No newline at end of file
+// Additional structs and methods for Grid and Player would be defined in their respective files.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn new_game(first: usize) -> Game {
+        let x = Player::new("Player X".to_string(), 'X');
+        let o = Player::new("Player O".to_string(), 'O');
+        Game::new(x, o, Rules::default(), first)
+    }
+
+    fn temp_path(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("tic-tac-toe-{}-{}.txt", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn saved_games_remember_who_moved_first() {
+        let path = temp_path("first-o");
+        let mut game = new_game(1);
+        game.play_turn(1, 1).unwrap();
+        game.play_turn(0, 0).unwrap();
+        game.save(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first o\n1 1\n0 0\n");
+
+        // Loading into a session where X moves first still replays O's opening move as O
+        let loaded = new_game(0).load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.first_side(), 1);
+        assert_eq!(loaded.get_grid().cells[1][1], Some('O'));
+        assert_eq!(loaded.get_grid().cells[0][0], Some('X'));
+        assert_eq!(loaded.current_side(), 1);
+    }
+
+    #[test]
+    fn files_without_a_first_line_use_the_current_first_side() {
+        let path = temp_path("no-first");
+        fs::write(&path, "1 1\n").unwrap();
+        let loaded = new_game(1).load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get_grid().cells[1][1], Some('O'));
+    }
+
+    #[test]
+    fn unknown_first_sides_are_rejected() {
+        let path = temp_path("bad-first");
+        fs::write(&path, "first z\n1 1\n").unwrap();
+        let err = new_game(0).load(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(err, format!("{} line 1: the first side must be 'x' or 'o', got 'first z'", path));
+    }
+}
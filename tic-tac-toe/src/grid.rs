@@ -1,28 +1,21 @@
-// This file defines the Grid struct which represents the 3x3 grid for the Tic-Tac-Toe game.
+// This file defines the Grid struct which represents the square grid for the Tic-Tac-Toe game.
 
+#[derive(Clone)]
 pub struct Grid {
-    pub cells: [[Option<char>; 3]; 3],
+    pub cells: Vec<Vec<Option<char>>>,
 }
 
 impl Grid {
-    // Creates a new empty grid
-    pub fn new() -> Self {
+    // Creates a new empty grid with the given number of rows and columns
+    pub fn new(size: usize) -> Self {
         Grid {
-            cells: [[None; 3]; 3],
+            cells: vec![vec![None; size]; size],
         }
     }
 
-    // Displays the current state of the grid
-    pub fn display(&self) {
-        for row in &self.cells {
-            for cell in row {
-                match cell {
-                    Some(mark) => print!(" {} ", mark),
-                    None => print!(" . "),
-                }
-            }
-            println!();
-        }
+    // Returns the number of rows (and columns) in the grid
+    pub fn size(&self) -> usize {
+        self.cells.len()
     }
 
     // Places a mark in the specified cell
@@ -30,8 +23,68 @@ impl Grid {
         self.cells[row][col] = Some(mark);
     }
 
+    // Removes the mark from the specified cell
+    pub fn clear_mark(&mut self, row: usize, col: usize) {
+        self.cells[row][col] = None;
+    }
+
     // Checks if a cell is occupied
     pub fn is_occupied(&self, row: usize, col: usize) -> bool {
         self.cells[row][col].is_some()
     }
-}
\ No newline at end of file
+
+    // Returns the coordinates of every empty cell, row by row
+    pub fn empty_cells(&self) -> Vec<(usize, usize)> {
+        let size = self.size();
+        (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .filter(|&(row, col)| !self.is_occupied(row, col))
+            .collect()
+    }
+
+    // Checks whether the mark at (row, col) is part of `length` identical marks in a row,
+    // column or diagonal. Only lines through that cell are examined, so call it after each move.
+    pub fn has_line_through(&self, row: usize, col: usize, length: usize) -> bool {
+        self.line_through(row, col, length).is_some()
+    }
+
+    // Returns the first and last cells of a line of at least `length` identical marks through
+    // (row, col), if there is one
+    pub fn line_through(&self, row: usize, col: usize, length: usize) -> Option<((usize, usize), (usize, usize))> {
+        let mark = self.cells[row][col]?;
+
+        // Horizontal, vertical and the two diagonals
+        [(0, 1), (1, 0), (1, 1), (1, -1)].iter().find_map(|&(dr, dc)| {
+            let forward = self.run_length(row, col, dr, dc, mark);
+            let back = self.run_length(row, col, -dr, -dc, mark);
+            if 1 + forward + back < length {
+                return None;
+            }
+            let step = |steps: usize, dr: isize, dc: isize| {
+                (
+                    (row as isize + dr * steps as isize) as usize,
+                    (col as isize + dc * steps as isize) as usize,
+                )
+            };
+            Some((step(back, -dr, -dc), step(forward, dr, dc)))
+        })
+    }
+
+    // Counts the consecutive `mark`s starting next to (row, col) in the direction (dr, dc)
+    fn run_length(&self, row: usize, col: usize, dr: isize, dc: isize, mark: char) -> usize {
+        let size = self.size() as isize;
+        let (mut r, mut c) = (row as isize + dr, col as isize + dc);
+        let mut count = 0;
+        while r >= 0 && r < size && c >= 0 && c < size && self.cells[r as usize][c as usize] == Some(mark) {
+            count += 1;
+            r += dr;
+            c += dc;
+        }
+        count
+    }
+
+    // Checks if every cell is occupied
+    pub fn is_full(&self) -> bool {
+        self.cells.iter().all(|row| row.iter().all(|cell| cell.is_some()))
+    }
+}
@@ -1,53 +1,134 @@
 // src/main.rs
 
+mod ai;
+mod command;
+mod coord;
 mod grid;
+mod options;
+mod output;
 mod player;
+mod render;
 mod game;
+mod score;
+mod script;
+mod svg;
+mod tui;
+mod turn;
 
-use game::Game;
-use player::Player;
+use command::{Command, GameCommand, MetaCommand};
+use options::Options;
+use output::Event;
+use score::Score;
 
+use std::env;
 use std::io;
+use std::process;
 
 fn main() {
-    let player_x = Player::new("Player X".to_string(), 'X');
-    let player_o = Player::new("Player O".to_string(), 'O');
-    let mut game = Game::new(player_x, player_o);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match Options::parse(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {}\n\nRun 'tic-tac-toe --help' for the list of options.", err);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", options::USAGE);
+        return;
+    }
+
+    let mut rng = options.rng();
 
-    println!("Welcome to Tic-Tac-Toe!");
+    // `--script` plays a game without prompts and reports the result in the exit code
+    if let Some(path) = &options.script {
+        process::exit(script::run(path, &options, &mut rng));
+    }
+
+    // `--tui` switches to the full-screen terminal interface
+    if options.tui {
+        if let Err(err) = tui::run(&options, &mut rng) {
+            eprintln!("Terminal error: {}", err);
+        }
+        return;
+    }
+
+    let output = options.output();
+    let mut game = options.new_game();
+    let mut score = Score::new();
+
+    output.info("Welcome to Tic-Tac-Toe! Type 'help' for a list of commands.");
+    output.state(&game, Event::Start);
     loop {
-        game.get_grid().display();
-        println!(
-            "{}'s turn. Enter your move (row and column):",
-            game.get_current_player().name
-        );
+        // Computer players move without prompting
+        if let Some(ai) = game.get_current_player().ai.filter(|_| !game.is_over()) {
+            if let Some((row, col)) = ai.choose_move(&game, &mut rng) {
+                turn::play(&mut game, &mut score, output.as_ref(), &mut rng, GameCommand::Move { row, col });
+            }
+            continue;
+        }
+        output.prompt(&game);
 
         let mut input = String::new();
-        io::stdin().read_line(&mut input).expect("Failed to read input");
-        let input: Vec<usize> = input
-            .trim()
-            .split_whitespace()
-            .filter_map(|x| x.parse().ok())
-            .collect();
-
-        if input.len() != 2 || input[0] > 2 || input[1] > 2 {
-            println!("Invalid input. Please enter row and column numbers between 0 and 2.");
-            continue;
+        if io::stdin().read_line(&mut input).expect("Failed to read input") == 0 {
+            break; // End of input
         }
 
-        match game.play_turn(input[0], input[1]) {
-            Ok(_) => {
-                if game.check_winner() {
-                    game.get_grid().display();
-                    println!("{} wins!", game.get_current_player().name);
-                    break;
-                } else if game.is_draw() {
-                    game.get_grid().display();
-                    println!("It's a draw!");
-                    break;
+        // Once a game is over, the players are offered a rematch
+        if game.is_over() {
+            match command::parse_rematch(&input) {
+                Some(true) => {
+                    game = game.rematch();
+                    output.state(&game, Event::New);
+                    continue;
                 }
+                Some(false) => break,
+                None => {}
             }
-            Err(err) => println!("{}", err),
+        }
+
+        let command = match command::parse(&input, game.get_grid().size()) {
+            Ok(command) => command,
+            Err(err) => {
+                output.error(&err);
+                continue;
+            }
+        };
+
+        match command {
+            Command::Game(command) => {
+                if game.is_over() {
+                    output.error("The game is over. Type 'new' to start another game.");
+                    continue;
+                }
+                turn::play(&mut game, &mut score, output.as_ref(), &mut rng, command);
+            }
+            Command::Meta(MetaCommand::New) => {
+                // Finished games alternate the starting side; an abandoned game is replayed as it began
+                game = if game.is_over() { game.rematch() } else { game.restart() };
+                output.state(&game, Event::New);
+            }
+            Command::Meta(MetaCommand::Save(path)) => match game.save(&path) {
+                Ok(()) => output.info(&format!("Game saved to {}.", path)),
+                Err(err) => output.error(&err),
+            },
+            Command::Meta(MetaCommand::Load(path)) => match game.load(&path) {
+                Ok(loaded) => {
+                    game = loaded;
+                    output.info(&format!("Game loaded from {}.", path));
+                    output.state(&game, Event::Load);
+                }
+                Err(err) => output.error(&err),
+            },
+            Command::Meta(MetaCommand::Export { path, whole_game }) => match svg::export(&game, &path, whole_game) {
+                Ok(()) => output.info(&format!("Exported to {}.", path)),
+                Err(err) => output.error(&err),
+            },
+            Command::Meta(MetaCommand::Score) => output.score(&game, &score),
+            Command::Meta(MetaCommand::Help) => output.info(command::HELP),
+            Command::Meta(MetaCommand::Quit) => break,
         }
     }
-}
\ No newline at end of file
+
+    output.summary(&game, &score);
+}
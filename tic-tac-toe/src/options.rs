@@ -0,0 +1,394 @@
+// This file parses the command-line options of the tic-tac-toe binary.
+// Options may be written as `--name value` or `--name=value`.
+
+use crate::ai::{Ai, AiKind, Difficulty};
+use crate::game::{Game, Rules, Variant};
+use crate::player::Player;
+use crate::output::{JsonOutput, Output, TextOutput};
+use crate::render::{self, Renderer};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+pub const USAGE: &str = "\
+Usage: tic-tac-toe [OPTIONS]
+
+Options:
+  --size <N>              Grid size, from 3 to 26 (default 3)
+  --win-length <N>        Marks in a row needed to end the game, from 3 to the grid size
+                          (default: the grid size, at most 5)
+  --variant <VARIANT>     Rules: 'standard' (a line wins) or 'misere' (a line loses)
+                          (default standard)
+  --first <SIDE>          Side that moves first: x or o (default x)
+  --name-x <NAME>         Name of the X player (default \"Player X\")
+  --name-o <NAME>         Name of the O player (default \"Player O\")
+  --mark-x <CHAR>         Mark drawn for the X player (default X)
+  --mark-o <CHAR>         Mark drawn for the O player (default O)
+  --ai-x <TYPE>           Let the computer play X: 'random' or 'minimax'
+  --ai-o <TYPE>           Let the computer play O: 'random' or 'minimax'
+  --difficulty-x <LEVEL>  Difficulty of the X computer player: easy, medium or hard (default hard)
+  --difficulty-o <LEVEL>  Difficulty of the O computer player: easy, medium or hard (default hard)
+  --seed <N>              Seed for the computer players' random choices, for reproducible games
+  --renderer <STYLE>      Board style: auto, ascii, unicode (no colour) or color (default auto)
+  --json                  Report every state change, message and error as one JSON object per line
+  --tui                   Use the full-screen terminal interface
+  --script <FILE>         Play the moves in FILE ('-' for standard input) without prompting;
+                          the exit code gives the result: 10 X wins, 11 O wins, 12 draw,
+                          13 illegal move, 14 unfinished game
+  -h, --help              Show this help and exit";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RendererChoice {
+    Auto,    // Unicode in a terminal, ASCII when redirected
+    Ascii,   // Plain ASCII
+    Unicode, // Box drawing without colour
+    Color,   // Box drawing with coloured marks, unless NO_COLOR is set
+}
+
+pub struct Options {
+    pub rules: Rules,
+    pub first: usize, // Side that moves first: 0 for X, 1 for O
+    pub names: [String; 2],
+    pub marks: [char; 2],
+    pub ais: [Option<Ai>; 2],
+    pub seed: Option<u64>,
+    pub renderer: RendererChoice,
+    pub json: bool,
+    pub tui: bool,
+    pub script: Option<String>, // Move script to play non-interactively, "-" for standard input
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            rules: Rules::default(),
+            first: 0,
+            names: ["Player X".to_string(), "Player O".to_string()],
+            marks: ['X', 'O'],
+            ais: [None, None],
+            seed: None,
+            renderer: RendererChoice::Auto,
+            json: false,
+            tui: false,
+            script: None,
+            help: false,
+        }
+    }
+}
+
+impl Options {
+    // Parses the arguments that follow the program name
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut size = None;
+        let mut win_length = None;
+        let mut kinds: [Option<AiKind>; 2] = [None, None];
+        let mut difficulties: [Option<Difficulty>; 2] = [None, None];
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+
+            // Flags take no value
+            match name {
+                "-h" | "--help" | "--json" | "--tui" if inline_value.is_some() => {
+                    return Err(format!("{} does not take a value", name));
+                }
+                "-h" | "--help" => {
+                    options.help = true;
+                    continue;
+                }
+                "--json" => {
+                    options.json = true;
+                    continue;
+                }
+                "--tui" => {
+                    options.tui = true;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| format!("{} needs a value", name))
+            };
+
+            match name {
+                "--size" => size = Some(parse_number(name, &value()?)?),
+                "--win-length" => win_length = Some(parse_number(name, &value()?)?),
+                "--variant" => options.rules.variant = parse_variant(&value()?)?,
+                "--first" => options.first = parse_side(name, &value()?)?,
+                "--name-x" => options.names[0] = parse_name(name, value()?)?,
+                "--name-o" => options.names[1] = parse_name(name, value()?)?,
+                "--mark-x" => options.marks[0] = parse_mark(name, &value()?)?,
+                "--mark-o" => options.marks[1] = parse_mark(name, &value()?)?,
+                "--ai-x" => kinds[0] = Some(parse_ai_kind(name, &value()?)?),
+                "--ai-o" => kinds[1] = Some(parse_ai_kind(name, &value()?)?),
+                "--difficulty-x" => difficulties[0] = Some(parse_difficulty(name, &value()?)?),
+                "--difficulty-o" => difficulties[1] = Some(parse_difficulty(name, &value()?)?),
+                "--seed" => options.seed = Some(parse_number(name, &value()?)?),
+                "--renderer" => options.renderer = parse_renderer(&value()?)?,
+                "--script" => options.script = Some(value()?),
+                _ if name.starts_with('-') => return Err(format!("unknown option '{}'", name)),
+                _ => return Err(format!("unexpected argument '{}'", arg)),
+            }
+        }
+
+        // Check the combinations
+        let size = size.unwrap_or(3);
+        if !(3..=26).contains(&size) {
+            return Err(format!("--size must be between 3 and 26, got {}", size));
+        }
+        let win_length = win_length.unwrap_or(size.min(5));
+        if win_length < 3 || win_length > size {
+            return Err(format!(
+                "--win-length must be between 3 and the grid size ({}), got {}",
+                size, win_length
+            ));
+        }
+        options.rules.size = size;
+        options.rules.win_length = win_length;
+
+        if options.marks[0] == options.marks[1] {
+            return Err(format!("--mark-x and --mark-o must differ, both are '{}'", options.marks[0]));
+        }
+
+        for (side, letter) in ["x", "o"].iter().enumerate() {
+            options.ais[side] = match (kinds[side], difficulties[side]) {
+                (Some(AiKind::Random), Some(_)) => {
+                    return Err(format!(
+                        "--difficulty-{} has no effect with --ai-{} random",
+                        letter, letter
+                    ));
+                }
+                (Some(kind), difficulty) => Some(Ai {
+                    kind,
+                    difficulty: difficulty.unwrap_or(Difficulty::Hard),
+                }),
+                (None, Some(_)) => {
+                    return Err(format!("--difficulty-{} needs --ai-{}", letter, letter));
+                }
+                (None, None) => None,
+            };
+        }
+
+        if options.tui && options.renderer != RendererChoice::Auto {
+            return Err("--renderer cannot be combined with --tui, which draws its own board".to_string());
+        }
+
+        if options.tui && options.script.is_some() {
+            return Err("--script cannot be combined with --tui".to_string());
+        }
+        if options.json && options.tui {
+            return Err("--json cannot be combined with --tui".to_string());
+        }
+        if options.json && options.renderer != RendererChoice::Auto {
+            return Err("--renderer has no effect with --json, which does not draw the board".to_string());
+        }
+
+        Ok(options)
+    }
+
+    // Creates a game with the configured players and rules
+    pub fn new_game(&self) -> Game {
+        let player = |side: usize| match self.ais[side] {
+            Some(ai) => Player::computer(self.names[side].clone(), self.marks[side], ai),
+            None => Player::new(self.names[side].clone(), self.marks[side]),
+        };
+        Game::new(player(0), player(1), self.rules, self.first)
+    }
+
+    // Creates the random number generator, seeded if --seed was given
+    pub fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    // Creates the output for the command loop and scripts: JSON lines or text
+    pub fn output(&self) -> Box<dyn Output> {
+        if self.json {
+            Box::new(JsonOutput)
+        } else {
+            Box::new(TextOutput {
+                renderer: self.renderer(),
+                errors_to_stderr: self.script.is_some(),
+            })
+        }
+    }
+
+    // Creates the board renderer
+    fn renderer(&self) -> Box<dyn Renderer> {
+        match self.renderer {
+            RendererChoice::Auto => render::default_renderer(self.marks),
+            RendererChoice::Ascii => Box::new(render::AsciiRenderer),
+            RendererChoice::Unicode => Box::new(render::UnicodeRenderer {
+                color: false,
+                marks: self.marks,
+            }),
+            RendererChoice::Color => Box::new(render::UnicodeRenderer {
+                color: !render::no_color(),
+                marks: self.marks,
+            }),
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a whole number, got '{}'", name, value))
+}
+
+fn parse_variant(value: &str) -> Result<Variant, String> {
+    match value.to_lowercase().as_str() {
+        "standard" => Ok(Variant::Standard),
+        "misere" | "misère" => Ok(Variant::Misere),
+        _ => Err(format!("--variant must be 'standard' or 'misere', got '{}'", value)),
+    }
+}
+
+fn parse_side(name: &str, value: &str) -> Result<usize, String> {
+    match value.to_lowercase().as_str() {
+        "x" => Ok(0),
+        "o" => Ok(1),
+        _ => Err(format!("{} must be 'x' or 'o', got '{}'", name, value)),
+    }
+}
+
+fn parse_name(name: &str, value: String) -> Result<String, String> {
+    if value.trim().is_empty() {
+        Err(format!("{} cannot be empty", name))
+    } else {
+        Ok(value)
+    }
+}
+
+fn parse_mark(name: &str, value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(mark), None) if !mark.is_whitespace() && mark != '.' => Ok(mark),
+        _ => Err(format!(
+            "{} must be a single visible character other than '.', got '{}'",
+            name, value
+        )),
+    }
+}
+
+fn parse_ai_kind(name: &str, value: &str) -> Result<AiKind, String> {
+    match value.to_lowercase().as_str() {
+        "random" => Ok(AiKind::Random),
+        "minimax" => Ok(AiKind::Minimax),
+        _ => Err(format!("{} must be 'random' or 'minimax', got '{}'", name, value)),
+    }
+}
+
+fn parse_difficulty(name: &str, value: &str) -> Result<Difficulty, String> {
+    match value.to_lowercase().as_str() {
+        "easy" => Ok(Difficulty::Easy),
+        "medium" => Ok(Difficulty::Medium),
+        "hard" => Ok(Difficulty::Hard),
+        _ => Err(format!("{} must be easy, medium or hard, got '{}'", name, value)),
+    }
+}
+
+fn parse_renderer(value: &str) -> Result<RendererChoice, String> {
+    match value.to_lowercase().as_str() {
+        "auto" => Ok(RendererChoice::Auto),
+        "ascii" => Ok(RendererChoice::Ascii),
+        "unicode" => Ok(RendererChoice::Unicode),
+        "color" | "colour" => Ok(RendererChoice::Color),
+        _ => Err(format!(
+            "--renderer must be auto, ascii, unicode or color, got '{}'",
+            value
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        Options::parse(&args)
+    }
+
+    fn error(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(_) => panic!("{:?} should be rejected", args),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn defaults_and_values() {
+        let options = parse(&[]).unwrap();
+        assert_eq!((options.rules.size, options.rules.win_length), (3, 3));
+        assert_eq!(options.ais, [None, None]);
+
+        let options = parse(&["--size", "10", "--ai-o=minimax", "--difficulty-o", "Easy", "--first", "o"]).unwrap();
+        assert_eq!((options.rules.size, options.rules.win_length), (10, 5));
+        assert_eq!(options.first, 1);
+        assert_eq!(options.ais[1], Some(Ai { kind: AiKind::Minimax, difficulty: Difficulty::Easy }));
+    }
+
+    #[test]
+    fn difficulties_need_a_searching_computer_player() {
+        assert_eq!(
+            error(&["--ai-x", "random", "--difficulty-x", "hard"]),
+            "--difficulty-x has no effect with --ai-x random"
+        );
+        assert_eq!(error(&["--difficulty-o", "easy"]), "--difficulty-o needs --ai-o");
+    }
+
+    #[test]
+    fn sizes_and_win_lengths_are_checked() {
+        assert_eq!(error(&["--size", "2"]), "--size must be between 3 and 26, got 2");
+        assert_eq!(error(&["--size", "27"]), "--size must be between 3 and 26, got 27");
+        assert_eq!(
+            error(&["--size", "4", "--win-length", "5"]),
+            "--win-length must be between 3 and the grid size (4), got 5"
+        );
+        assert_eq!(error(&["--size", "big"]), "--size expects a whole number, got 'big'");
+    }
+
+    #[test]
+    fn marks_must_differ() {
+        assert_eq!(error(&["--mark-o", "X"]), "--mark-x and --mark-o must differ, both are 'X'");
+        assert_eq!(
+            error(&["--mark-x", "."]),
+            "--mark-x must be a single visible character other than '.', got '.'"
+        );
+    }
+
+    #[test]
+    fn interfaces_that_do_not_mix_are_rejected() {
+        assert_eq!(
+            error(&["--tui", "--renderer", "ascii"]),
+            "--renderer cannot be combined with --tui, which draws its own board"
+        );
+        assert_eq!(error(&["--tui", "--script", "moves.txt"]), "--script cannot be combined with --tui");
+        assert_eq!(error(&["--json", "--tui"]), "--json cannot be combined with --tui");
+        assert_eq!(
+            error(&["--json", "--renderer=color"]),
+            "--renderer has no effect with --json, which does not draw the board"
+        );
+    }
+
+    #[test]
+    fn malformed_arguments_are_rejected() {
+        assert_eq!(error(&["--colour"]), "unknown option '--colour'");
+        assert_eq!(error(&["3"]), "unexpected argument '3'");
+        assert_eq!(error(&["--seed"]), "--seed needs a value");
+        assert_eq!(error(&["--json=yes"]), "--json does not take a value");
+    }
+}
@@ -0,0 +1,276 @@
+// This file reports what happens in a game, either as text for people or as JSON lines.
+// The game loops call an Output for every state change, message and error instead of printing
+// directly, so `--json` can replace all of the human text with one JSON object per line.
+
+use crate::coord;
+use crate::game::Game;
+use crate::render::Renderer;
+use crate::score::Score;
+
+use std::cmp::Ordering;
+
+// The state changes that are reported along with the new board
+#[derive(Clone, Copy)]
+pub enum Event {
+    Start,
+    Move { row: usize, col: usize },
+    Undo,
+    Resign,
+    New,
+    Load,
+}
+
+pub trait Output {
+    // Reports a state change: the board, the side to move and the result if the game ended
+    fn state(&self, game: &Game, event: Event);
+    // Asks the current player for a move, or for a new game once the game is over
+    fn prompt(&self, game: &Game);
+    fn hint(&self, game: &Game, row: usize, col: usize);
+    fn score(&self, game: &Game, score: &Score);
+    // Reports the tally of the whole session when the players quit
+    fn summary(&self, game: &Game, score: &Score);
+    // Reports anything else worth telling, such as a saved file or the help text
+    fn info(&self, message: &str);
+    fn error(&self, message: &str);
+}
+
+// Human-readable text, with the board drawn by a renderer
+pub struct TextOutput {
+    pub renderer: Box<dyn Renderer>,
+    pub errors_to_stderr: bool, // Scripted games keep errors apart from the game record
+}
+
+// One JSON object per line, for scripts and dashboards
+pub struct JsonOutput;
+
+impl Output for TextOutput {
+    fn state(&self, game: &Game, event: Event) {
+        match event {
+            Event::Move { row, col } => {
+                println!(
+                    "{} plays {}.",
+                    game.player(game.side_of(row, col)).name,
+                    coord::to_algebraic(row, col, game.get_grid().size())
+                );
+            }
+            Event::Resign => {
+                if let Some(message) = resign_message(game) {
+                    println!("{}", message);
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        print!("{}", self.renderer.render(game.get_grid()));
+        if let Event::Move { .. } = event {
+            if let Some(message) = result_message(game) {
+                println!("{}", message);
+            }
+        }
+    }
+
+    fn prompt(&self, game: &Game) {
+        if game.is_over() {
+            println!(
+                "Play again? Type 'y' for a rematch with {} moving first, or 'n' to quit:",
+                game.player(1 - game.first_side()).name
+            );
+        } else {
+            println!(
+                "{}'s turn. Enter your move (e.g. b2, a keypad digit, or row and column):",
+                game.get_current_player().name
+            );
+        }
+    }
+
+    fn hint(&self, game: &Game, row: usize, col: usize) {
+        println!(
+            "Hint: try {} (row {}, column {}).",
+            coord::to_algebraic(row, col, game.get_grid().size()),
+            row,
+            col
+        );
+    }
+
+    fn score(&self, game: &Game, score: &Score) {
+        println!("{}", score.summary(&game.player(0).name, &game.player(1).name));
+    }
+
+    fn summary(&self, game: &Game, score: &Score) {
+        let (name_x, name_o) = (&game.player(0).name, &game.player(1).name);
+        let leader = match score.wins_x.cmp(&score.wins_o) {
+            Ordering::Greater => format!("{} leads.", name_x),
+            Ordering::Less => format!("{} leads.", name_o),
+            Ordering::Equal => "The session is tied.".to_string(),
+        };
+        println!("Thanks for playing! Games played: {}.", score.games());
+        println!("{}", score.summary(name_x, name_o));
+        println!("{}", leader);
+    }
+
+    fn info(&self, message: &str) {
+        println!("{}", message);
+    }
+
+    fn error(&self, message: &str) {
+        if self.errors_to_stderr {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    }
+}
+
+impl Output for JsonOutput {
+    fn state(&self, game: &Game, event: Event) {
+        let size = game.get_grid().size();
+        let cell = |(row, col): (usize, usize)| string(&coord::to_algebraic(row, col, size));
+
+        let mut fields = vec![("event", string(event_name(event)))];
+        if let Event::Start = event {
+            let names = (0..2).map(|side| string(&game.player(side).name)).collect();
+            fields.push(("players", array(names)));
+        }
+        if let Event::Move { row, col } = event {
+            fields.push(("side", string(side_name(game.side_of(row, col)))));
+            fields.push(("cell", cell((row, col))));
+        }
+
+        // Rows from the top, with '.' for empty cells
+        let board = game
+            .get_grid()
+            .cells
+            .iter()
+            .map(|row| string(&row.iter().map(|cell| cell.unwrap_or('.')).collect::<String>()))
+            .collect();
+        fields.push(("board", array(board)));
+        fields.push(("moves", array(game.moves().iter().map(|&move_| cell(move_)).collect())));
+
+        let (to_move, legal_moves) = if game.is_over() {
+            ("null".to_string(), Vec::new())
+        } else {
+            let legal = game.get_grid().empty_cells().into_iter().map(cell).collect();
+            (string(side_name(game.current_side())), legal)
+        };
+        fields.push(("to_move", to_move));
+        fields.push(("legal_moves", array(legal_moves)));
+
+        let result = match game.winner() {
+            Some(side) => string(side_name(side)),
+            None if game.is_draw() => string("draw"),
+            None => "null".to_string(),
+        };
+        fields.push(("result", result));
+
+        println!("{}", object(&fields));
+    }
+
+    fn prompt(&self, _game: &Game) {
+        // The side to move is already part of every state line
+    }
+
+    fn hint(&self, game: &Game, row: usize, col: usize) {
+        let cell = coord::to_algebraic(row, col, game.get_grid().size());
+        println!("{}", object(&[("event", string("hint")), ("cell", string(&cell))]));
+    }
+
+    fn score(&self, _game: &Game, score: &Score) {
+        println!(
+            "{}",
+            object(&[
+                ("event", string("score")),
+                ("x", score.wins_x.to_string()),
+                ("o", score.wins_o.to_string()),
+                ("draws", score.draws.to_string()),
+            ])
+        );
+    }
+
+    fn summary(&self, _game: &Game, score: &Score) {
+        println!(
+            "{}",
+            object(&[
+                ("event", string("summary")),
+                ("games", score.games().to_string()),
+                ("x", score.wins_x.to_string()),
+                ("o", score.wins_o.to_string()),
+                ("draws", score.draws.to_string()),
+            ])
+        );
+    }
+
+    fn info(&self, message: &str) {
+        println!("{}", object(&[("event", string("info")), ("message", string(message))]));
+    }
+
+    fn error(&self, message: &str) {
+        println!("{}", object(&[("event", string("error")), ("message", string(message))]));
+    }
+}
+
+// Announces the result of a game that ended on a move, or None if it goes on
+pub fn result_message(game: &Game) -> Option<String> {
+    match game.winner() {
+        Some(winner) => Some(format!("{} wins!", game.player(winner).name)),
+        None if game.is_draw() => Some("It's a draw!".to_string()),
+        None => None,
+    }
+}
+
+// Announces a resignation, or None if nobody has resigned
+pub fn resign_message(game: &Game) -> Option<String> {
+    game.winner()
+        .map(|winner| format!("{} resigns. {} wins!", game.player(1 - winner).name, game.player(winner).name))
+}
+
+fn event_name(event: Event) -> &'static str {
+    match event {
+        Event::Start => "start",
+        Event::Move { .. } => "move",
+        Event::Undo => "undo",
+        Event::Resign => "resign",
+        Event::New => "new",
+        Event::Load => "load",
+    }
+}
+
+fn side_name(side: usize) -> &'static str {
+    if side == 0 {
+        "x"
+    } else {
+        "o"
+    }
+}
+
+// Builds a JSON object from keys and already encoded values
+fn object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}:{}", string(key), value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+// Builds a JSON array from already encoded values
+fn array(values: Vec<String>) -> String {
+    format!("[{}]", values.join(","))
+}
+
+// Encodes a JSON string, escaping quotes, backslashes and control characters
+fn string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if c.is_control() => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -1,20 +1,33 @@
 // This file defines the Player struct which represents a player in the game.
-// It includes fields for the player's name and mark (X or O).
+// It includes fields for the player's name, mark (X or O) and, for computer players, the AI.
 // It exports methods such as new and get_mark.
 
+use crate::ai::Ai;
+
+#[derive(Clone)]
 pub struct Player {
     pub name: String,
     pub mark: char,
+    pub ai: Option<Ai>, // None for a human player
 }
 
 impl Player {
-    // Creates a new Player with the given name and mark.
+    // Creates a new human Player with the given name and mark.
     pub fn new(name: String, mark: char) -> Self {
-        Player { name, mark }
+        Player { name, mark, ai: None }
+    }
+
+    // Creates a new computer Player that picks its moves with the given AI.
+    pub fn computer(name: String, mark: char, ai: Ai) -> Self {
+        Player {
+            name,
+            mark,
+            ai: Some(ai),
+        }
     }
 
     // Returns the mark of the player (X or O).
     pub fn get_mark(&self) -> char {
         self.mark
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,186 @@
+// This file defines the renderers that turn a Grid into text for the terminal.
+// Renderers return a String instead of printing, so callers decide where the output goes.
+
+use crate::coord;
+use crate::grid::Grid;
+
+use crossterm::style::{Color, Stylize};
+use std::env;
+use std::io::{self, IsTerminal};
+
+// Mark colours, matching the red and blue used by the Bevy version of the game
+const X_COLOR: Color = Color::Rgb { r: 255, g: 0, b: 0 };
+const O_COLOR: Color = Color::Rgb { r: 0, g: 0, b: 255 };
+
+pub trait Renderer {
+    // Renders the grid, including row and column labels, as one or more lines of text
+    fn render(&self, grid: &Grid) -> String;
+}
+
+// Plain ASCII output: " X ", " O " and " . " cells
+pub struct AsciiRenderer;
+
+// Unicode box drawing, with marks optionally coloured using ANSI escape codes
+pub struct UnicodeRenderer {
+    pub color: bool,
+    pub marks: [char; 2], // Marks of Player X (drawn red) and Player O (drawn blue)
+}
+
+impl Renderer for AsciiRenderer {
+    fn render(&self, grid: &Grid) -> String {
+        let size = grid.size();
+        let mut out = String::new();
+
+        for (i, row) in grid.cells.iter().enumerate() {
+            out += &format!("{:>2} ", size - i);
+            for cell in row {
+                out += &format!(" {} ", cell.unwrap_or('.'));
+            }
+            out += "\n";
+        }
+        out += "   ";
+        out += &file_labels(size, 3);
+        out += "\n";
+        out
+    }
+}
+
+impl Renderer for UnicodeRenderer {
+    fn render(&self, grid: &Grid) -> String {
+        let size = grid.size();
+        let mut out = border(size, '┌', '┬', '┐');
+
+        for (i, row) in grid.cells.iter().enumerate() {
+            out += &format!("{:>2} │", size - i);
+            for cell in row {
+                out += &format!(" {} │", self.mark(*cell));
+            }
+            out += "\n";
+            out += &if i + 1 < size {
+                border(size, '├', '┼', '┤')
+            } else {
+                border(size, '└', '┴', '┘')
+            };
+        }
+        out += "    ";
+        out += &file_labels(size, 4);
+        out += "\n";
+        out
+    }
+}
+
+impl UnicodeRenderer {
+    fn mark(&self, cell: Option<char>) -> String {
+        match cell {
+            Some(mark) if self.color => {
+                let color = if mark == self.marks[0] { X_COLOR } else { O_COLOR };
+                mark.with(color).bold().to_string()
+            }
+            Some(mark) => mark.to_string(),
+            None => " ".to_string(),
+        }
+    }
+}
+
+// Picks the default renderer. Terminals get Unicode box drawing, coloured unless NO_COLOR is set;
+// redirected output gets plain ASCII.
+pub fn default_renderer(marks: [char; 2]) -> Box<dyn Renderer> {
+    if io::stdout().is_terminal() {
+        Box::new(UnicodeRenderer {
+            color: !no_color(),
+            marks,
+        })
+    } else {
+        Box::new(AsciiRenderer)
+    }
+}
+
+// Follows the NO_COLOR convention (https://no-color.org): any non-empty value disables colour
+pub fn no_color() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+fn border(size: usize, left: char, middle: char, right: char) -> String {
+    let segments = vec!["───"; size].join(&middle.to_string());
+    format!("   {}{}{}\n", left, segments, right)
+}
+
+fn file_labels(size: usize, width: usize) -> String {
+    (0..size)
+        .map(|col| format!("{:^width$}", coord::file_label(col), width = width))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(size: usize, marks: &[(usize, usize, char)]) -> Grid {
+        let mut grid = Grid::new(size);
+        for &(row, col, mark) in marks {
+            grid.place_mark(row, col, mark);
+        }
+        grid
+    }
+
+    // Character column of `c` on `line`, counting characters rather than bytes
+    fn column(line: &str, c: char) -> usize {
+        line.chars().position(|x| x == c).unwrap()
+    }
+
+    #[test]
+    fn ascii_boards_have_ranks_and_files() {
+        let board = AsciiRenderer.render(&grid(3, &[(0, 0, 'X'), (1, 1, 'O')]));
+        assert_eq!(board, " 3  X  .  . \n 2  .  O  . \n 1  .  .  . \n    a  b  c\n");
+    }
+
+    #[test]
+    fn labels_line_up_on_large_boards() {
+        let size = 12;
+        let board = grid(size, &[(0, size - 1, 'X'), (size - 1, 0, 'O'), (2, 9, 'X')]);
+        let renderers: [Box<dyn Renderer>; 2] = [
+            Box::new(AsciiRenderer),
+            Box::new(UnicodeRenderer { color: false, marks: ['X', 'O'] }),
+        ];
+
+        for renderer in renderers {
+            let text = renderer.render(&board);
+            let lines: Vec<&str> = text.lines().collect();
+            let files = lines.last().unwrap();
+            let row = |rank: &str| {
+                *lines
+                    .iter()
+                    .find(|line| line[..3].trim() == rank)
+                    .unwrap()
+            };
+
+            // Two-digit ranks are right-aligned with the single-digit ones
+            assert!(row("12").starts_with("12 "));
+            assert!(row("1").starts_with(" 1 "));
+            // Each mark sits above the letter of its column
+            assert_eq!(column(row("12"), 'X'), column(files, 'l'));
+            assert_eq!(column(row("1"), 'O'), column(files, 'a'));
+            assert_eq!(column(row("10"), 'X'), column(files, 'j'));
+        }
+    }
+
+    #[test]
+    fn colour_marks_use_ansi_codes() {
+        let renderer = UnicodeRenderer { color: true, marks: ['X', 'O'] };
+        let board = renderer.render(&grid(3, &[(0, 0, 'X'), (2, 2, 'O')]));
+        assert!(board.contains(&'X'.with(X_COLOR).bold().to_string()));
+        assert!(board.contains(&'O'.with(O_COLOR).bold().to_string()));
+        assert!(board.contains("\x1b[38;2;255;0;0m"));
+        assert!(board.contains("\x1b[38;2;0;0;255m"));
+    }
+
+    #[test]
+    fn plain_unicode_has_no_escape_codes() {
+        let renderer = UnicodeRenderer { color: false, marks: ['X', 'O'] };
+        let board = renderer.render(&grid(3, &[(0, 0, 'X'), (2, 2, 'O')]));
+        assert!(!board.contains('\x1b'));
+        assert!(board.contains("│ X │"));
+    }
+}
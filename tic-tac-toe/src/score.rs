@@ -0,0 +1,40 @@
+// This file defines the Score struct which tallies results across the games of a session.
+
+pub struct Score {
+    pub wins_x: u32,
+    pub wins_o: u32,
+    pub draws: u32,
+}
+
+impl Score {
+    // Creates an empty tally
+    pub fn new() -> Self {
+        Score {
+            wins_x: 0,
+            wins_o: 0,
+            draws: 0,
+        }
+    }
+
+    // Records the result of a finished game: the winning side (0 for X, 1 for O), or None for a draw
+    pub fn record(&mut self, winner: Option<usize>) {
+        match winner {
+            Some(0) => self.wins_x += 1,
+            Some(_) => self.wins_o += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    // Returns the number of finished games
+    pub fn games(&self) -> u32 {
+        self.wins_x + self.wins_o + self.draws
+    }
+
+    // Formats the tally for display, using the names of the X and O players
+    pub fn summary(&self, name_x: &str, name_o: &str) -> String {
+        format!(
+            "Scores: {} - {}, {} - {}, Draws - {}",
+            name_x, self.wins_x, name_o, self.wins_o, self.draws
+        )
+    }
+}
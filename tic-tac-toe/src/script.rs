@@ -0,0 +1,133 @@
+// This file runs a game from a script of moves without prompting, for tests and CI.
+// Each line holds one game command (a move, `undo`, `hint` or `resign`) or `quit`; blank lines
+// and lines starting with '#' are skipped. Computer players move on their own as usual.
+// The exit code of the process tells how the game ended.
+
+use crate::ai;
+use crate::command::{self, Command, GameCommand, MetaCommand};
+use crate::coord;
+use crate::game::Game;
+use crate::options::Options;
+use crate::output::Event;
+
+use rand::rngs::StdRng;
+use std::fs;
+use std::io::{self, Read};
+
+pub const EXIT_X_WINS: i32 = 10;
+pub const EXIT_O_WINS: i32 = 11;
+pub const EXIT_DRAW: i32 = 12;
+pub const EXIT_ILLEGAL: i32 = 13; // An illegal move or a line that is not a command
+pub const EXIT_UNFINISHED: i32 = 14; // The script ended before the game did
+pub const EXIT_IO_ERROR: i32 = 1;
+
+// Plays the script at `path` ("-" for standard input) and returns the exit code
+pub fn run(path: &str, options: &Options, rng: &mut StdRng) -> i32 {
+    let output = options.output();
+    let script = match read_script(path) {
+        Ok(script) => script,
+        Err(err) => {
+            output.error(&err);
+            return EXIT_IO_ERROR;
+        }
+    };
+
+    let mut game = options.new_game();
+    let size = game.get_grid().size();
+    output.state(&game, Event::Start);
+
+    let mut lines = script
+        .lines()
+        .enumerate()
+        .map(|(number, line)| (number + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let mut number = 0; // Line of the script being played
+    while !game.is_over() {
+        // Computer players move without reading the script
+        let command = if let Some(ai) = game.get_current_player().ai {
+            match ai.choose_move(&game, rng) {
+                Some((row, col)) => GameCommand::Move { row, col },
+                None => break,
+            }
+        } else {
+            let line = match lines.next() {
+                Some((next, line)) => {
+                    number = next;
+                    line
+                }
+                None => break,
+            };
+            match command::parse(line, size) {
+                Ok(Command::Game(command)) => command,
+                Ok(Command::Meta(MetaCommand::Quit)) => break,
+                Ok(Command::Meta(_)) => {
+                    output.error(&format!(
+                        "line {}: only game commands and 'quit' can be used in a script",
+                        number
+                    ));
+                    return EXIT_ILLEGAL;
+                }
+                Err(err) => {
+                    output.error(&format!("line {}: {}", number, err));
+                    return EXIT_ILLEGAL;
+                }
+            }
+        };
+
+        match command {
+            GameCommand::Move { row, col } => {
+                if let Err(err) = game.play_turn(row, col) {
+                    let name = &game.get_current_player().name;
+                    let cell = coord::to_algebraic(row, col, size);
+                    output.error(&format!("line {}: {} cannot play {}: {}", number, name, cell, err));
+                    return EXIT_ILLEGAL;
+                }
+                output.state(&game, Event::Move { row, col });
+            }
+            GameCommand::Undo => {
+                if let Err(err) = game.undo_turn() {
+                    output.error(&format!("line {}: {}", number, err));
+                    return EXIT_ILLEGAL;
+                }
+                output.state(&game, Event::Undo);
+            }
+            GameCommand::Hint => {
+                if let Some((row, col)) = ai::best_move(&game, rng) {
+                    output.hint(&game, row, col);
+                }
+            }
+            GameCommand::Resign => {
+                game.resign();
+                output.state(&game, Event::Resign);
+            }
+        }
+    }
+
+    if !game.is_over() {
+        output.info("The script ended before the game was over.");
+    }
+    exit_code(&game)
+}
+
+fn read_script(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut script = String::new();
+        io::stdin()
+            .read_to_string(&mut script)
+            .map_err(|err| format!("Could not read the script from standard input: {}", err))?;
+        Ok(script)
+    } else {
+        fs::read_to_string(path).map_err(|err| format!("Could not read the script {}: {}", path, err))
+    }
+}
+
+// Returns the exit code for the result of the game
+fn exit_code(game: &Game) -> i32 {
+    match game.winner() {
+        Some(0) => EXIT_X_WINS,
+        Some(_) => EXIT_O_WINS,
+        None if game.is_draw() => EXIT_DRAW,
+        None => EXIT_UNFINISHED,
+    }
+}
@@ -0,0 +1,55 @@
+// This file exports boards as standalone SVG images, for documentation and bug reports.
+// A single position is drawn with its coordinates and the winning line; a whole game becomes a
+// filmstrip with the board after every move. The drawing itself is shared with the Bevy version
+// in `tic_tac_toe_common::svg`; this file only turns a `Game` into its boards.
+
+use crate::coord;
+use crate::game::Game;
+use crate::grid::Grid;
+
+use std::fs;
+use tic_tac_toe_common::svg::{self, Frame, Style};
+
+// Red and blue marks, matching the terminal renderer, in the marks the players chose
+fn style(game: &Game) -> Style {
+    Style {
+        marks: [game.player(0).get_mark(), game.player(1).get_mark()],
+        ..Style::default()
+    }
+}
+
+// Renders the current position
+pub fn board(game: &Game) -> String {
+    svg::board(&game.get_grid().cells, game.winning_line(), &style(game))
+}
+
+// Renders the board after every move of the game, left to right and top to bottom
+pub fn filmstrip(game: &Game) -> String {
+    let size = game.get_grid().size();
+    let marks = [game.player(0).get_mark(), game.player(1).get_mark()];
+    let moves = game.moves();
+
+    let mut grid = Grid::new(size);
+    let mut frames = Vec::new();
+    for (index, &(row, col)) in moves.iter().enumerate() {
+        // Sides alternate from the one that moved first
+        let mark = marks[(game.first_side() + index) % 2];
+        grid.place_mark(row, col, mark);
+        frames.push(Frame {
+            cells: grid.cells.clone(),
+            line: if index + 1 == moves.len() { game.winning_line() } else { None },
+            caption: format!("{}. {} {}", index + 1, mark, coord::to_algebraic(row, col, size)),
+        });
+    }
+    if frames.is_empty() {
+        // An empty game still shows its board
+        frames.push(Frame { cells: grid.cells, line: None, caption: "Start".to_string() });
+    }
+    svg::filmstrip(&frames, &style(game))
+}
+
+// Writes the current position, or with `whole_game` the filmstrip of the game, to a file
+pub fn export(game: &Game, path: &str, whole_game: bool) -> Result<(), String> {
+    let image = if whole_game { filmstrip(game) } else { board(game) };
+    fs::write(path, image).map_err(|err| format!("Could not export to {}: {}", path, err))
+}
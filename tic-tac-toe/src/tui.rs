@@ -0,0 +1,304 @@
+// This file implements the full-screen terminal interface.
+// A highlighted cursor is moved over the grid with the arrow keys or hjkl and Enter places a mark.
+// Side panels show whose turn it is, the scores and the move history, and the screen is redrawn
+// in place after every key press. The terminal is restored on exit and on panic.
+
+use crate::ai;
+use crate::command::GameCommand;
+use crate::coord;
+use crate::game::Game;
+use crate::options::Options;
+use crate::output::{self, Event, Output};
+use crate::score::Score;
+use crate::turn;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use rand::rngs::StdRng;
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::panic;
+
+const HISTORY_LINES: usize = 15; // Most recent moves shown in the history panel
+
+struct Tui<'a> {
+    game: Game,
+    score: Score,
+    cursor: (usize, usize),
+    status: StatusLine,
+    rng: &'a mut StdRng,
+}
+
+// The message line under the board. Moves are played through `turn::play`, which reports them
+// through this Output; the board and panels are redrawn from the game itself.
+#[derive(Default)]
+struct StatusLine {
+    message: RefCell<String>,
+}
+
+impl StatusLine {
+    fn set(&self, message: String) {
+        *self.message.borrow_mut() = message;
+    }
+
+    fn clear(&self) {
+        self.message.borrow_mut().clear();
+    }
+}
+
+impl Output for StatusLine {
+    fn state(&self, game: &Game, event: Event) {
+        let message = match event {
+            Event::Move { .. } => {
+                output::result_message(game).map(|result| format!("{} Press 'n' for a rematch.", result))
+            }
+            Event::Resign => output::resign_message(game),
+            _ => None,
+        };
+        if let Some(message) = message {
+            self.set(message);
+        }
+    }
+
+    fn prompt(&self, _game: &Game) {
+        // The status panel shows whose turn it is
+    }
+
+    fn hint(&self, _game: &Game, _row: usize, _col: usize) {
+        // Hints move the cursor instead, see `Tui::hint`
+    }
+
+    fn score(&self, _game: &Game, _score: &Score) {
+        // The scores are always on screen
+    }
+
+    fn summary(&self, _game: &Game, _score: &Score) {
+        // Printed after leaving the full-screen interface
+    }
+
+    fn info(&self, message: &str) {
+        self.set(message.to_string());
+    }
+
+    fn error(&self, message: &str) {
+        self.set(message.to_string());
+    }
+}
+
+// Puts the terminal back into its normal state when dropped
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+fn restore_terminal() {
+    let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+// Runs the terminal interface until the player quits
+pub fn run(options: &Options, rng: &mut StdRng) -> io::Result<()> {
+    // Restore the terminal before the panic message is printed, so it is readable
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+    }));
+
+    let guard = TerminalGuard::enter()?;
+    let centre = options.rules.size / 2;
+    let mut tui = Tui {
+        game: options.new_game(),
+        score: Score::new(),
+        cursor: (centre, centre),
+        status: StatusLine::default(),
+        rng,
+    };
+
+    loop {
+        tui.play_computer_moves();
+        tui.draw(&mut io::stdout())?;
+
+        let key = match event::read()? {
+            event::Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        tui.status.clear();
+
+        match key.code {
+            // Raw mode turns Ctrl+C into a key press instead of a signal
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Up | KeyCode::Char('k') => tui.move_cursor(-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => tui.move_cursor(1, 0),
+            KeyCode::Left | KeyCode::Char('h') => tui.move_cursor(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => tui.move_cursor(0, 1),
+            KeyCode::Enter | KeyCode::Char(' ') => tui.place(),
+            KeyCode::Char('u') => tui.undo(),
+            KeyCode::Char('?') => tui.hint(),
+            KeyCode::Char('r') => tui.resign(),
+            KeyCode::Char('n') => {
+                // Finished games alternate the starting side; an abandoned game is replayed as it began
+                tui.game = if tui.game.is_over() { tui.game.rematch() } else { tui.game.restart() };
+                tui.status.set(format!("New game. {} moves first.", tui.game.get_current_player().name));
+            }
+            _ => {}
+        }
+    }
+
+    // Leave the full-screen interface before printing the session summary
+    drop(guard);
+    options.output().summary(&tui.game, &tui.score);
+    Ok(())
+}
+
+impl Tui<'_> {
+    fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let size = self.game.get_grid().size() as isize;
+        let (row, col) = self.cursor;
+        self.cursor = (
+            (row as isize + rows).rem_euclid(size) as usize,
+            (col as isize + cols).rem_euclid(size) as usize,
+        );
+    }
+
+    fn place(&mut self) {
+        if self.game.is_over() {
+            self.status.set("The game is over. Press 'n' for a rematch.".to_string());
+            return;
+        }
+        let (row, col) = self.cursor;
+        self.play(GameCommand::Move { row, col });
+    }
+
+    fn play(&mut self, command: GameCommand) {
+        turn::play(&mut self.game, &mut self.score, &self.status, self.rng, command);
+    }
+
+    // Lets computer players move until it is a human's turn or the game ends
+    fn play_computer_moves(&mut self) {
+        while !self.game.is_over() {
+            let ai = match self.game.get_current_player().ai {
+                Some(ai) => ai,
+                None => break,
+            };
+            match ai.choose_move(&self.game, self.rng) {
+                Some((row, col)) => self.play(GameCommand::Move { row, col }),
+                None => break,
+            }
+        }
+    }
+
+    fn undo(&mut self) {
+        if self.game.is_over() {
+            self.status.set("The game is over. Press 'n' for a rematch.".to_string());
+            return;
+        }
+        self.play(GameCommand::Undo);
+    }
+
+    fn hint(&mut self) {
+        if self.game.is_over() {
+            return;
+        }
+        if let Some(cell) = ai::best_move(&self.game, self.rng) {
+            self.cursor = cell;
+            self.status.set("Hint: the cursor is on the suggested cell.".to_string());
+        }
+    }
+
+    fn resign(&mut self) {
+        if !self.game.is_over() {
+            self.play(GameCommand::Resign);
+        }
+    }
+
+    // Redraws the screen by overwriting every line in place; clearing the whole screen first
+    // makes the terminal flicker
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let grid = self.game.get_grid();
+        let size = grid.size();
+        let panel_column = 5 + 3 * size as u16; // Side panels start to the right of the board
+        let bottom = 4 + (size.max(3 + HISTORY_LINES)) as u16; // Line of the message
+
+        // Side panels, by screen line
+        let mut panel = vec![String::new(); bottom as usize];
+        panel[2] = if self.game.is_over() {
+            "Game over".to_string()
+        } else {
+            let player = self.game.get_current_player();
+            format!("Turn: {} ({})", player.name, player.get_mark())
+        };
+        panel[3] = format!(
+            "{} {}  {} {}  Draws {}",
+            self.game.player(0).get_mark(),
+            self.score.wins_x,
+            self.game.player(1).get_mark(),
+            self.score.wins_o,
+            self.score.draws
+        );
+        panel[5] = "Moves:".to_string();
+
+        // Move history, one numbered move per line, most recent moves only
+        let moves = self.game.moves();
+        let skipped = moves.len().saturating_sub(HISTORY_LINES);
+        for (line, (i, &(row, col))) in moves.iter().enumerate().skip(skipped).enumerate() {
+            let mark = grid.cells[row][col].unwrap_or('?');
+            panel[6 + line] = format!("{:>3}. {} {}", i + 1, mark, coord::to_algebraic(row, col, size));
+        }
+
+        for y in 0..bottom + 2 {
+            queue!(out, MoveTo(0, y))?;
+            if y == 0 {
+                queue!(out, Print("Tic-Tac-Toe"))?;
+            } else if let Some(row) = (y as usize).checked_sub(2).filter(|&row| row < size) {
+                // Board, with the cursor cell in reverse video
+                queue!(out, Print(format!("{:>2} ", size - row)))?;
+                for (col, cell) in grid.cells[row].iter().enumerate() {
+                    let text = format!(" {} ", cell.unwrap_or('.'));
+                    if (row, col) == self.cursor {
+                        queue!(
+                            out,
+                            SetAttribute(Attribute::Reverse),
+                            Print(text),
+                            SetAttribute(Attribute::Reset)
+                        )?;
+                    } else {
+                        queue!(out, Print(text))?;
+                    }
+                }
+            } else if y as usize == 2 + size {
+                let files: String = (0..size).map(|col| format!(" {} ", coord::file_label(col))).collect();
+                queue!(out, Print(format!("   {}", files)))?;
+            } else if y == bottom {
+                queue!(out, Print(self.status.message.borrow().as_str()))?;
+            } else if y == bottom + 1 {
+                queue!(out, Print("Arrows/hjkl move  Enter place  u undo  ? hint  r resign  n new  q quit"))?;
+            }
+
+            // Erase what is left of the previous frame on this line, then draw the panel over it
+            queue!(out, Clear(ClearType::UntilNewLine))?;
+            if let Some(text) = panel.get(y as usize).filter(|text| !text.is_empty()) {
+                queue!(out, MoveTo(panel_column, y), Print(text))?;
+            }
+        }
+        queue!(out, Clear(ClearType::FromCursorDown))?;
+
+        out.flush()
+    }
+}
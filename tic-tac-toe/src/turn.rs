@@ -0,0 +1,40 @@
+// This file applies game commands to the game in progress. The command loop and the terminal
+// interface both play through `play`, so moves, undos and resignations are scored and reported
+// the same way whichever interface is in use.
+
+use crate::ai;
+use crate::command::GameCommand;
+use crate::game::Game;
+use crate::output::{Event, Output};
+use crate::score::Score;
+
+use rand::rngs::StdRng;
+
+// Applies a game command, records the result in the score if the game ends and reports it
+pub fn play(game: &mut Game, score: &mut Score, output: &dyn Output, rng: &mut StdRng, command: GameCommand) {
+    match command {
+        GameCommand::Move { row, col } => match game.play_turn(row, col) {
+            Ok(_) => {
+                output.state(game, Event::Move { row, col });
+                if game.is_over() {
+                    score.record(game.winner());
+                }
+            }
+            Err(err) => output.error(&err),
+        },
+        GameCommand::Undo => match game.undo_turn() {
+            Ok(()) => output.state(game, Event::Undo),
+            Err(err) => output.error(&err),
+        },
+        GameCommand::Hint => {
+            if let Some((row, col)) = ai::best_move(game, rng) {
+                output.hint(game, row, col);
+            }
+        }
+        GameCommand::Resign => {
+            game.resign();
+            output.state(game, Event::Resign);
+            score.record(game.winner());
+        }
+    }
+}
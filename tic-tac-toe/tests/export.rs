@@ -0,0 +1,47 @@
+// Exports boards as SVG images through the interactive game and checks the files it writes.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Plays `input` in an interactive game, without a terminal
+fn play(input: &str) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tic-tac-toe"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to start the game");
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    assert!(child.wait().expect("failed to run the game").success());
+}
+
+#[test]
+fn exports_the_board_and_the_game() {
+    let dir = env::temp_dir();
+    let board = dir.join(format!("tic-tac-toe-board-{}.svg", std::process::id()));
+    let game = dir.join(format!("tic-tac-toe-game-{}.svg", std::process::id()));
+    let input = format!(
+        "b2\na1\nc3\na3\na2\nc1\nc2\nexport svg {}\nexport svg {} game\nn\n",
+        board.display(),
+        game.display()
+    );
+    play(&input);
+
+    let board_svg = fs::read_to_string(&board).unwrap();
+    assert!(board_svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(board_svg.trim_end().ends_with("</svg>"));
+    assert_eq!(board_svg.matches(">X</text>").count(), 4);
+    assert_eq!(board_svg.matches(">O</text>").count(), 3);
+    assert!(board_svg.contains("stroke-width=\"6\"")); // The winning line
+
+    // One captioned board per move
+    let game_svg = fs::read_to_string(&game).unwrap();
+    assert!(game_svg.contains(">1. X b2</text>"));
+    assert!(game_svg.contains(">7. X c2</text>"));
+    assert_eq!(game_svg.matches("stroke-width=\"6\"").count(), 1);
+
+    fs::remove_file(board).unwrap();
+    fs::remove_file(game).unwrap();
+}
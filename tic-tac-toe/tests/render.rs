@@ -0,0 +1,47 @@
+// Draws boards through the binary and checks when the marks are coloured.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Plays one move with the given renderer and returns what was printed. `no_color` sets or
+// removes the NO_COLOR environment variable.
+fn play(renderer: &str, no_color: Option<&str>) -> String {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_tic-tac-toe"));
+    command
+        .args(["--renderer", renderer])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    match no_color {
+        Some(value) => command.env("NO_COLOR", value),
+        None => command.env_remove("NO_COLOR"),
+    };
+    let mut child = command.spawn().expect("failed to start the game");
+    child.stdin.take().unwrap().write_all(b"b2\nquit\n").unwrap();
+    let output = child.wait_with_output().expect("failed to run the game");
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn the_colour_renderer_colours_the_marks() {
+    let board = play("color", None);
+    assert!(board.contains("\x1b[38;2;255;0;0m"));
+    assert!(board.contains('X'));
+}
+
+#[test]
+fn no_color_turns_the_colours_off() {
+    let board = play("color", Some("1"));
+    assert!(!board.contains('\x1b'));
+    assert!(board.contains("│ X │"));
+
+    // An empty value does not count, as the convention says
+    assert!(play("color", Some("")).contains('\x1b'));
+}
+
+#[test]
+fn other_renderers_never_colour() {
+    assert!(!play("unicode", None).contains('\x1b'));
+    assert!(!play("ascii", None).contains('\x1b'));
+}
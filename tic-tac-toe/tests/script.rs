@@ -0,0 +1,101 @@
+// Plays whole games through the binary with --script and checks the exit codes and output.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+// Runs the game with the given options, feeding `script` on standard input
+fn run(script: &str, args: &[&str]) -> Output {
+    run_with(&["--script", "-", "--renderer", "ascii"], script, args)
+}
+
+// Runs the game with JSON output, which cannot be combined with --renderer
+fn run_json(script: &str, args: &[&str]) -> Output {
+    run_with(&["--script", "-", "--json"], script, args)
+}
+
+fn run_with(options: &[&str], script: &str, args: &[&str]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tic-tac-toe"))
+        .args(options)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start the game");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    child.wait_with_output().expect("failed to run the game")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn x_wins() {
+    let output = run("b2\na1\nc3\na3\na2\nc1\nc2\n", &[]);
+    assert_eq!(output.status.code(), Some(10));
+    assert!(stdout(&output).ends_with("Player X wins!\n"));
+}
+
+#[test]
+fn o_wins() {
+    // Notations can be mixed: keypad digits and row/column pairs
+    let output = run("1\n5\n3\n8\n0 0\n2 1\n", &["--name-o", "Olive"]);
+    assert_eq!(output.status.code(), Some(11));
+    assert!(stdout(&output).ends_with("Olive wins!\n"));
+}
+
+#[test]
+fn draw() {
+    let output = run("a1\nb2\nc3\nb1\nb3\na3\nc1\nc2\na2\n", &[]);
+    assert_eq!(output.status.code(), Some(12));
+    assert!(stdout(&output).ends_with("It's a draw!\n"));
+}
+
+#[test]
+fn illegal_move() {
+    let output = run("# opening\nb2\nb2\n", &[]);
+    assert_eq!(output.status.code(), Some(13));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("line 3"), "unexpected error: {}", stderr);
+}
+
+#[test]
+fn unfinished_game() {
+    let output = run("b2\na1\n", &[]);
+    assert_eq!(output.status.code(), Some(14));
+}
+
+#[test]
+fn resign_and_undo() {
+    let output = run("b2\na1\nundo\nresign\n", &[]);
+    assert_eq!(output.status.code(), Some(10));
+    assert!(stdout(&output).contains("Player O resigns."));
+}
+
+#[test]
+fn computer_players_are_reproducible() {
+    let args = ["--ai-x", "minimax", "--difficulty-x", "easy", "--ai-o", "random", "--seed", "42"];
+    let first = run("", &args);
+    let second = run("", &args);
+    assert!(matches!(first.status.code(), Some(10..=12)));
+    assert_eq!(stdout(&first), stdout(&second));
+}
+
+#[test]
+fn json_output() {
+    let output = run_json("b2\na1\nc3\na3\na2\nc1\nc2\n", &[]);
+    assert_eq!(output.status.code(), Some(10));
+    let stdout = stdout(&output);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 8); // The start state and one line per move
+    assert!(lines.iter().all(|line| line.starts_with('{') && line.ends_with('}')));
+    assert!(lines[0].starts_with(r#"{"event":"start","players":["Player X","Player O"]"#));
+    assert!(lines[7].contains(r#""board":["O.X","XXX","O.O"]"#));
+    assert!(lines[7].ends_with(r#""to_move":null,"legal_moves":[],"result":"x"}"#));
+}
@@ -0,0 +1,75 @@
+// Plays whole sessions through the interactive game: rematches, the alternating first player and
+// the summary printed on quitting.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Plays `input` in an interactive game, without a terminal, and returns what was printed
+fn play(input: &str, args: &[&str]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tic-tac-toe"))
+        .args(["--renderer", "ascii"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to start the game");
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().expect("failed to run the game");
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+// The lines that report moves, results and the session, without the boards and turn prompts
+fn story(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with(' ') && !line.contains("'s turn."))
+        .collect()
+}
+
+const X_WINS: &str = "b2\na1\nc3\na3\na2\nc1\nc2\n";
+
+#[test]
+fn rematches_alternate_the_first_player() {
+    // X wins the first game; O opens the rematch and wins it along the a file
+    let input = format!("{}y\na1\nb1\na2\nb2\na3\nn\n", X_WINS);
+    let output = play(&input, &[]);
+    let story = story(&output);
+
+    let first_win = story.iter().position(|&line| line == "Player X wins!").unwrap();
+    assert_eq!(
+        story[first_win + 1],
+        "Play again? Type 'y' for a rematch with Player O moving first, or 'n' to quit:"
+    );
+    assert_eq!(story[first_win + 2], "Player O plays a1.");
+
+    assert_eq!(
+        &story[story.len() - 5..],
+        [
+            "Player O wins!",
+            "Play again? Type 'y' for a rematch with Player X moving first, or 'n' to quit:",
+            "Thanks for playing! Games played: 2.",
+            "Scores: Player X - 1, Player O - 1, Draws - 0",
+            "The session is tied.",
+        ]
+    );
+}
+
+#[test]
+fn the_summary_names_the_leader() {
+    let output = play(&format!("{}n\n", X_WINS), &["--name-x", "Xena"]);
+    assert!(output.ends_with(
+        "Thanks for playing! Games played: 1.\nScores: Xena - 1, Player O - 0, Draws - 0\nXena leads.\n"
+    ));
+}
+
+#[test]
+fn unfinished_games_do_not_count() {
+    // Quitting mid-game, and a game abandoned with 'new', leave the tally alone
+    let output = play("b2\nnew\na1\nquit\n", &[]);
+    assert!(output.contains("Player X plays a1."));
+    assert!(output.ends_with(
+        "Thanks for playing! Games played: 0.\nScores: Player X - 0, Player O - 0, Draws - 0\nThe session is tied.\n"
+    ));
+}